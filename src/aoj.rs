@@ -0,0 +1,351 @@
+//! Aizu Online Judge. Problems are read from its JSON API instead of the pages,
+//! which are rendered by JavaScript.
+
+use crate::parser::{self, Parser};
+use crate::site::{Site, SiteFuture};
+use serde::{Deserialize, Serialize};
+
+/// The API of problems and descriptions
+pub const API_URL: &str = "https://judgeapi.u-aizu.ac.jp/";
+/// The API of test cases
+pub const DATA_URL: &str = "https://judgedat.u-aizu.ac.jp/";
+/// The pages of problems for people
+pub const PROBLEM_URL: &str = "https://onlinejudge.u-aizu.ac.jp/problems/";
+
+// `GET /problems/{id}`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProblemEntry {
+    id: String,
+    name: String,
+    /// Seconds
+    problem_time_limit: f64,
+    /// Kilobytes
+    problem_memory_limit: u64,
+}
+
+// An item of `GET /testcases/samples/{id}`
+#[derive(Deserialize)]
+struct SampleEntry {
+    serial: u64,
+    #[serde(rename = "in")]
+    input: String,
+    out: String,
+}
+
+// `GET /resources/descriptions/{lang}/{id}`
+#[derive(Deserialize)]
+struct DescriptionEntry {
+    html: String,
+}
+
+// The responses of the APIs about a problem, which are fetched together as a document of `AojSite`
+#[derive(Serialize, Deserialize)]
+struct Document {
+    problem: String,
+    samples: String,
+    description: Option<String>,
+}
+
+pub fn is_aoj_host(host: &str) -> bool {
+    host == "onlinejudge.u-aizu.ac.jp" || host == "judge.u-aizu.ac.jp"
+}
+
+/// The problem ID of a problem URL.
+/// e.g `https://onlinejudge.u-aizu.ac.jp/courses/lesson/1/ALDS1/1/ALDS1_1_B` -> `ALDS1_1_B`,
+/// `http://judge.u-aizu.ac.jp/onlinejudge/description.jsp?id=0001` -> `0001`
+pub fn problem_id(url: &url::Url) -> Option<String> {
+    if let Some((_, id)) = url.query_pairs().find(|(key, _)| key == "id") {
+        return Some(id.to_string());
+    }
+    let paths = url.path_segments()?.collect::<Vec<_>>();
+    match paths.as_slice() {
+        ["problems", id] => Some(id.to_string()),
+        ["courses", .., id] | ["challenges", .., id] if !id.is_empty() => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+pub struct AojParser {
+    problem: ProblemEntry,
+    samples: Vec<SampleEntry>,
+    /// The statement in HTML
+    description: Option<String>,
+}
+
+impl AojParser {
+    /// Builds a parser from the responses of the problem, samples and description APIs
+    pub fn new(
+        problem_json: &str,
+        samples_json: &str,
+        description_json: Option<&str>,
+    ) -> Result<AojParser, anyhow::Error> {
+        let mut samples: Vec<SampleEntry> = serde_json::from_str(samples_json)?;
+        samples.sort_by_key(|sample| sample.serial);
+        let description = description_json
+            .map(serde_json::from_str::<DescriptionEntry>)
+            .transpose()?
+            .map(|description| description.html);
+        Ok(AojParser {
+            problem: serde_json::from_str(problem_json)?,
+            samples,
+            description,
+        })
+    }
+    /// Builds a parser from a document of `fetch_document`
+    pub fn from_document(document: &str) -> Result<AojParser, anyhow::Error> {
+        let document: Document = serde_json::from_str(document)?;
+        AojParser::new(
+            &document.problem,
+            &document.samples,
+            document.description.as_deref(),
+        )
+    }
+    /// Fetches the problem `problem_id` from the APIs at `api_url` and `data_url`.
+    /// A missing description isn't an error because only the float tolerance is read from it.
+    pub async fn fetch_document(
+        client: &reqwest::Client,
+        api_url: &url::Url,
+        data_url: &url::Url,
+        problem_id: &str,
+    ) -> Result<String, anyhow::Error> {
+        let get = |url: url::Url| async move {
+            client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await
+        };
+        let problem = get(api_url.join(&format!("problems/{}", problem_id))?)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get the problem {}: {}", problem_id, e))?;
+        let samples = get(data_url.join(&format!("testcases/samples/{}", problem_id))?).await?;
+        let description = get(api_url.join(&format!("resources/descriptions/en/{}", problem_id))?)
+            .await
+            .ok();
+        Ok(serde_json::to_string(&Document {
+            problem,
+            samples,
+            description,
+        })?)
+    }
+    pub async fn fetch(
+        client: &reqwest::Client,
+        api_url: &url::Url,
+        data_url: &url::Url,
+        problem_id: &str,
+    ) -> Result<AojParser, anyhow::Error> {
+        let document = AojParser::fetch_document(client, api_url, data_url, problem_id).await?;
+        AojParser::from_document(&document)
+    }
+}
+
+impl Parser for AojParser {
+    fn problem_name(&self) -> Option<String> {
+        Some(format!("{} {}", self.problem.id, self.problem.name))
+    }
+    // AOJ has no contests. e.g `ALDS1_1` of `ALDS1_1_B` and `Volume 0` of `0001`
+    fn contest_name(&self) -> Option<String> {
+        let id = &self.problem.id;
+        match id.rsplit_once('_') {
+            Some((course, _)) => Some(course.to_string()),
+            None => id
+                .parse::<u64>()
+                .ok()
+                .map(|number| format!("Volume {}", number / 100))
+                .or_else(|| Some(id.clone())),
+        }
+    }
+    fn sample_cases(&self) -> Vec<(String, String)> {
+        self.samples
+            .iter()
+            .map(|sample| (sample.input.clone(), sample.out.clone()))
+            .collect()
+    }
+    fn time_limit_ms(&self) -> Option<u64> {
+        Some((self.problem.problem_time_limit * 1000.0).round() as u64)
+    }
+    fn memory_limit_mb(&self) -> Option<u64> {
+        Some(self.problem.problem_memory_limit / 1024)
+    }
+    fn float_tolerance(&self) -> Option<f64> {
+        let html = scraper::Html::parse_fragment(self.description.as_ref()?);
+        parser::parse_float_tolerance(&html.root_element().text().collect::<String>())
+    }
+}
+
+pub struct AojSite;
+
+impl Site for AojSite {
+    fn name(&self) -> &'static str {
+        "AOJ"
+    }
+    fn matches(&self, host: &str) -> bool {
+        is_aoj_host(host)
+    }
+    // `description.jsp?id=0001` and `/courses/.../ALDS1_1_B` are saved in the directories of `/problems/{id}`
+    fn normalize_url(&self, url: &url::Url) -> url::Url {
+        problem_id(url)
+            .and_then(|problem_id| {
+                url::Url::parse(PROBLEM_URL)
+                    .and_then(|base| base.join(&problem_id))
+                    .ok()
+            })
+            .unwrap_or_else(|| url.clone())
+    }
+    // `ALDS1_1_B` -> ("ALDS1_1", "B"). Volume problems such as `0001` -> ("problems", "0001")
+    fn contest_and_index(&self, url: &url::Url) -> Option<(String, String)> {
+        let problem_id = problem_id(url)?;
+        Some(match problem_id.rsplit_once('_') {
+            Some((course, index)) => (course.to_string(), index.to_string()),
+            None => ("problems".to_string(), problem_id),
+        })
+    }
+    fn fetch_document<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        url: &'a url::Url,
+    ) -> SiteFuture<'a, String> {
+        Box::pin(async move {
+            let problem_id =
+                problem_id(url).ok_or_else(|| anyhow::anyhow!("Invalid problem URL: {}", url))?;
+            AojParser::fetch_document(
+                client,
+                &url::Url::parse(API_URL)?,
+                &url::Url::parse(DATA_URL)?,
+                &problem_id,
+            )
+            .await
+        })
+    }
+    fn parser(&self, document: &str) -> Result<Box<dyn Parser>, anyhow::Error> {
+        Ok(Box::new(AojParser::from_document(document)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{problem_id, AojParser, AojSite};
+    use crate::layout;
+    use crate::parser::Parser;
+    use crate::site::Site;
+    use crate::util;
+    use std::path::Path;
+
+    #[test]
+    fn test_fixture() {
+        let parser = AojParser::new(
+            include_str!("../tests/fixtures/aoj/ALDS1_1_B_problem.json"),
+            include_str!("../tests/fixtures/aoj/ALDS1_1_B_samples.json"),
+            Some(include_str!(
+                "../tests/fixtures/aoj/ALDS1_1_B_description.json"
+            )),
+        )
+        .unwrap();
+        assert_eq!(
+            parser.problem_name(),
+            Some("ALDS1_1_B Greatest Common Divisor".to_string())
+        );
+        assert_eq!(parser.contest_name(), Some("ALDS1_1".to_string()));
+        assert_eq!(parser.time_limit_ms(), Some(1000));
+        assert_eq!(parser.memory_limit_mb(), Some(128));
+        assert_eq!(parser.float_tolerance(), None);
+        assert_eq!(
+            parser.sample_cases(),
+            vec![
+                ("54 20\n".to_string(), "2\n".to_string()),
+                ("147 105\n".to_string(), "21\n".to_string())
+            ]
+        );
+
+        let parser = AojParser::new(
+            include_str!("../tests/fixtures/aoj/ALDS1_1_B_problem.json"),
+            "[]",
+            Some(include_str!(
+                "../tests/fixtures/aoj/CGL_1_A_description.json"
+            )),
+        )
+        .unwrap();
+        assert_eq!(parser.float_tolerance(), Some(1e-8));
+        assert!(parser.sample_cases().is_empty());
+    }
+
+    #[test]
+    fn test_problem_id() {
+        let id = |url: &str| problem_id(&url::Url::parse(url).unwrap());
+        assert_eq!(
+            id("https://onlinejudge.u-aizu.ac.jp/problems/ALDS1_1_B"),
+            Some("ALDS1_1_B".to_string())
+        );
+        assert_eq!(
+            id("https://onlinejudge.u-aizu.ac.jp/courses/lesson/1/ALDS1/1/ALDS1_1_B"),
+            Some("ALDS1_1_B".to_string())
+        );
+        assert_eq!(
+            id("https://onlinejudge.u-aizu.ac.jp/challenges/sources/JAG/Prelim/2881"),
+            Some("2881".to_string())
+        );
+        assert_eq!(
+            id("http://judge.u-aizu.ac.jp/onlinejudge/description.jsp?id=0001&lang=jp"),
+            Some("0001".to_string())
+        );
+        assert_eq!(id("https://onlinejudge.u-aizu.ac.jp/home"), None);
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        let dir = |url: &str| {
+            let url = AojSite.normalize_url(&url::Url::parse(url).unwrap());
+            let info = util::problem_info(url.as_str(), "", "", None);
+            layout::problem_dir(Path::new("/root"), &layout::default_layout(), &info).unwrap()
+        };
+        let first = dir("http://judge.u-aizu.ac.jp/onlinejudge/description.jsp?id=0001");
+        let second = dir("http://judge.u-aizu.ac.jp/onlinejudge/description.jsp?id=0002&lang=jp");
+        assert_eq!(
+            first,
+            Path::new("/root/onlinejudge.u-aizu.ac.jp/problems/0001")
+        );
+        assert_ne!(first, second);
+        assert_eq!(
+            dir("https://onlinejudge.u-aizu.ac.jp/courses/lesson/1/ALDS1/1/ALDS1_1_B"),
+            dir("https://onlinejudge.u-aizu.ac.jp/problems/ALDS1_1_B")
+        );
+        assert_eq!(
+            AojSite
+                .normalize_url(&url::Url::parse("https://onlinejudge.u-aizu.ac.jp/home").unwrap())
+                .as_str(),
+            "https://onlinejudge.u-aizu.ac.jp/home"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch() {
+        use crate::mock_server::{MockServer, Response};
+
+        let server = MockServer::start(vec![
+            (
+                ("GET", "/problems/ALDS1_1_B"),
+                Response::ok(include_str!("../tests/fixtures/aoj/ALDS1_1_B_problem.json")),
+            ),
+            (
+                ("GET", "/testcases/samples/ALDS1_1_B"),
+                Response::ok(include_str!("../tests/fixtures/aoj/ALDS1_1_B_samples.json")),
+            ),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+        let parser = AojParser::fetch(&client, &server.base_url, &server.base_url, "ALDS1_1_B")
+            .await
+            .unwrap();
+        assert_eq!(parser.sample_cases().len(), 2);
+        // The description is optional
+        assert_eq!(parser.float_tolerance(), None);
+        assert!(
+            AojParser::fetch(&client, &server.base_url, &server.base_url, "XXX")
+                .await
+                .is_err()
+        );
+    }
+}
@@ -1,6 +1,9 @@
 use std::collections::BTreeMap;
 
-use crate::parser::Parser;
+use crate::parser::{self, Parser};
+use crate::site::{self, Site, SiteFuture};
+use crate::submit::{self, Language, LanguageOption, SubmissionResult, SubmitError, Verdict};
+use crate::util::ProblemInfo;
 use easy_scraper::Pattern;
 pub struct AtCoderParser {
     html: String,
@@ -36,6 +39,23 @@ impl Parser for AtCoderParser {
                 .map_or(vec![], |sample_cases| sample_cases)
         }
     }
+    fn time_limit_ms(&self) -> Option<u64> {
+        self.limit("Time Limit", "実行時間制限")
+            .and_then(|limit| parser::parse_time_limit_ms(&limit))
+    }
+    fn memory_limit_mb(&self) -> Option<u64> {
+        self.limit("Memory Limit", "メモリ制限")
+            .and_then(|limit| parser::parse_memory_limit_mb(&limit))
+    }
+    fn float_tolerance(&self) -> Option<f64> {
+        let statement_selector = scraper::Selector::parse(r#"div[id="task-statement"]"#).unwrap();
+        self.document
+            .select(&statement_selector)
+            .next()
+            .and_then(|statement| {
+                parser::parse_float_tolerance(&statement.text().collect::<String>())
+            })
+    }
 }
 
 impl AtCoderParser {
@@ -82,6 +102,20 @@ impl AtCoderParser {
         }
     }
 
+    // e.g "Time Limit: 2 sec / Memory Limit: 1024 MB" or "実行時間制限: 2 sec / メモリ制限: 1024 MB"
+    fn limit(&self, en_label: &str, ja_label: &str) -> Option<String> {
+        let selector = scraper::Selector::parse("p").unwrap();
+        self.document
+            .select(&selector)
+            .map(|p| p.text().collect::<String>())
+            .find(|text| text.contains(en_label) || text.contains(ja_label))
+            .and_then(|text| {
+                text.split('/')
+                    .find(|part| part.contains(en_label) || part.contains(ja_label))
+                    .map(|part| part.trim().to_string())
+            })
+    }
+
     pub fn csrf_token(&self) -> Option<String> {
         let selector = scraper::Selector::parse(r#"input[name="csrf_token"]"#).unwrap();
         if let Some(element) = self.document.select(&selector).next() {
@@ -91,6 +125,107 @@ impl AtCoderParser {
         }
         None
     }
+    /// The user name in `var userScreenName = "..."` of a page. It's empty unless logged in
+    pub fn user_screen_name(&self) -> Option<String> {
+        let start = self.html.find("var userScreenName = \"")? + "var userScreenName = \"".len();
+        let name = self.html[start..].split('"').next()?;
+        if name.is_empty() {
+            None
+        } else {
+            Some(name.to_string())
+        }
+    }
+    /// Languages in the `<select>` of a submit page
+    pub fn language_options(&self) -> Vec<LanguageOption> {
+        let selector =
+            scraper::Selector::parse(r#"select[name="data.LanguageId"] > option"#).unwrap();
+        let mut options: Vec<LanguageOption> = vec![];
+        // Each task has its own select with the same languages
+        for option in self.document.select(&selector) {
+            if let Some(id) = option.value().attr("value") {
+                if !id.is_empty() && options.iter().all(|option| option.id != id) {
+                    options.push(LanguageOption {
+                        id: id.to_string(),
+                        name: option.text().collect::<String>().trim().to_string(),
+                    });
+                }
+            }
+        }
+        options
+    }
+    /// Task screen names in the `<select>` of a submit page. e.g `abc154_a`
+    pub fn task_screen_names(&self) -> Vec<String> {
+        let selector =
+            scraper::Selector::parse(r#"select[name="data.TaskScreenName"] > option"#).unwrap();
+        self.document
+            .select(&selector)
+            .filter_map(|option| option.value().attr("value"))
+            .map(|name| name.to_string())
+            .collect()
+    }
+    /// Submissions on "My Submissions" (`/contests/<contest>/submissions/me`), newest first
+    pub fn submission_results(&self) -> Vec<SubmissionResult> {
+        let row_selector = scraper::Selector::parse("table > tbody > tr").unwrap();
+        let td_selector = scraper::Selector::parse("td").unwrap();
+        let a_selector = scraper::Selector::parse("a").unwrap();
+        let label_selector = scraper::Selector::parse("span.label").unwrap();
+        let text =
+            |element: scraper::ElementRef| element.text().collect::<String>().trim().to_string();
+
+        self.document
+            .select(&row_selector)
+            .filter_map(|row| {
+                let tds = row.select(&td_selector).collect::<Vec<_>>();
+                // Submission Time, Task, User, Language, Score, Code Size, Status, (Exec Time, Memory,) Detail
+                if tds.len() < 8 {
+                    return None;
+                }
+                let task = tds[1].select(&a_selector).next()?;
+                let label = tds[6].select(&label_selector).next()?;
+                // "AC", "WJ", "3/12" or "3/12 WA" while judging
+                let mut verdict = Verdict::Judging;
+                let (mut test_case, mut total_test_cases) = (None, None);
+                for token in text(label).split_whitespace() {
+                    if let Some((judged, total)) = token.split_once('/') {
+                        test_case = judged.parse::<u64>().ok();
+                        total_test_cases = total.parse::<u64>().ok();
+                    } else {
+                        verdict = Verdict::from_abbreviation(token);
+                    }
+                }
+                if test_case.is_some() {
+                    verdict = Verdict::Judging;
+                }
+                // Exec Time and Memory are merged into Status while judging and on CE
+                let (exec_time_ms, memory_kb) = if tds.len() >= 10 {
+                    (
+                        submit::leading_number(&text(tds[7])),
+                        submit::leading_number(&text(tds[8])),
+                    )
+                } else {
+                    (None, None)
+                };
+                let id = tds
+                    .last()?
+                    .select(&a_selector)
+                    .next()
+                    .and_then(|detail| detail.value().attr("href"))
+                    .and_then(|href| href.rsplit('/').next())?
+                    .to_string();
+                Some(SubmissionResult {
+                    id,
+                    problem_path: task.value().attr("href")?.to_string(),
+                    problem_name: text(task),
+                    language: text(tds[3]),
+                    verdict,
+                    test_case,
+                    total_test_cases,
+                    exec_time_ms,
+                    memory_kb,
+                })
+            })
+            .collect()
+    }
     fn extract_sample_cases(&self) -> Vec<(String, String)> {
         // new format
         let en_pattern = Pattern::new(
@@ -272,6 +407,180 @@ impl AtCoderParser {
     }
 }
 
+/// Submits `source` to a task and returns the URL of "My Submissions".
+/// `task_url` is a problem URL such as `https://atcoder.jp/contests/abc154/tasks/abc154_a`.
+pub async fn submit(
+    client: &reqwest::Client,
+    task_url: &url::Url,
+    language: &Language,
+    source: &str,
+) -> Result<url::Url, anyhow::Error> {
+    // /contests/abc154/tasks/abc154_a
+    let paths = task_url
+        .path_segments()
+        .map(|paths| paths.collect::<Vec<_>>())
+        .unwrap_or_default();
+    let (contest, task_screen_name) = match paths.as_slice() {
+        ["contests", contest, "tasks", task_screen_name] => (*contest, *task_screen_name),
+        _ => return Err(anyhow::anyhow!("Not a task URL: {}", task_url)),
+    };
+    let mut submit_url = task_url.join(&format!("/contests/{}/submit", contest))?;
+
+    let mut page_url = submit_url.clone();
+    page_url.set_query(Some(&format!("taskScreenName={}", task_screen_name)));
+    let html = client.get(page_url).send().await?.text().await?;
+    let parser = AtCoderParser::new(&html);
+    let csrf_token = parser.csrf_token();
+    let language_options = parser.language_options();
+    let csrf_token = match csrf_token {
+        Some(csrf_token) if !language_options.is_empty() => csrf_token,
+        _ => return Err(SubmitError::NotLoggedIn.into()),
+    };
+    if !parser
+        .task_screen_names()
+        .iter()
+        .any(|name| name == task_screen_name)
+    {
+        return Err(anyhow::anyhow!(
+            "{} isn't a task of {}",
+            task_screen_name,
+            contest
+        ));
+    }
+    let language_id = language
+        .resolve(&language_options)
+        .ok_or_else(|| anyhow::anyhow!("{} isn't available on {}", language, submit_url))?;
+
+    let params = [
+        ("data.TaskScreenName", task_screen_name),
+        ("data.LanguageId", &language_id),
+        ("sourceCode", source),
+        ("csrf_token", &csrf_token),
+    ];
+    let resp = client.post(submit_url.clone()).form(&params).send().await?;
+    // A successful submission is redirected to "My Submissions"
+    if !resp.url().path().ends_with("/submissions/me") {
+        return Err(anyhow::anyhow!(
+            "Failed to submit. The response was {} {}",
+            resp.status(),
+            resp.url()
+        ));
+    }
+    submit_url.set_path(resp.url().path());
+    Ok(submit_url)
+}
+
+/// Logs in with the form on `login_url` (e.g `https://atcoder.jp/login`).
+pub async fn login(
+    client: &reqwest::Client,
+    login_url: &url::Url,
+    username: &str,
+    password: &str,
+) -> Result<(), anyhow::Error> {
+    let html = client.get(login_url.clone()).send().await?.text().await?;
+    let csrf_token = AtCoderParser::new(&html)
+        .csrf_token()
+        .ok_or_else(|| anyhow::anyhow!("No csrf_token in {}", login_url))?;
+    let params = [
+        ("username", username),
+        ("password", password),
+        ("csrf_token", &csrf_token),
+    ];
+    let resp = client.post(login_url.clone()).form(&params).send().await?;
+    // AtCoder redirects to the login page again on failure
+    if resp.url().path() == login_url.path() {
+        return Err(anyhow::anyhow!("Failed to login AtCoder"));
+    }
+    Ok(())
+}
+
+pub struct AtCoderSite;
+
+impl Site for AtCoderSite {
+    fn name(&self) -> &'static str {
+        "AtCoder"
+    }
+    fn matches(&self, host: &str) -> bool {
+        host == "atcoder.jp"
+    }
+    // A contest page is read as its task list. e.g /contests/abc154 -> /contests/abc154/tasks
+    fn normalize_url(&self, url: &url::Url) -> url::Url {
+        let mut paths: Vec<_> = url.path().trim_end_matches('/').split('/').collect();
+        if !paths.contains(&"tasks") {
+            paths.push("tasks");
+        }
+        let mut url = url.clone();
+        url.set_path(&paths.join("/"));
+        url
+    }
+    fn parser(&self, document: &str) -> Result<Box<dyn Parser>, anyhow::Error> {
+        Ok(Box::new(AtCoderParser::new(document)))
+    }
+    fn problem_url_list(&self, url: &url::Url, document: &str) -> Option<Vec<url::Url>> {
+        if url.path_segments()?.next_back() != Some("tasks") {
+            return None;
+        }
+        let url_list = AtCoderParser::new(document)
+            .problem_url_list()
+            .unwrap_or_default();
+        Some(site::join_all(url, &url_list))
+    }
+    // /contests/abc154/tasks/abc154_a -> ("abc154", "a")
+    fn contest_and_index(&self, url: &url::Url) -> Option<(String, String)> {
+        match url.path_segments()?.collect::<Vec<_>>().as_slice() {
+            ["contests", contest, "tasks", task] => {
+                let index = task.strip_prefix(&format!("{}_", contest)).unwrap_or(task);
+                Some((contest.to_string(), index.to_string()))
+            }
+            _ => None,
+        }
+    }
+    fn login_url(&self) -> Option<&'static str> {
+        Some("https://atcoder.jp/login")
+    }
+    fn login<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        login_url: &'a url::Url,
+        username: &'a str,
+        password: &'a str,
+    ) -> SiteFuture<'a, ()> {
+        Box::pin(login(client, login_url, username, password))
+    }
+    fn submit<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        info: &'a ProblemInfo,
+        language: &'a Language,
+        source: &'a str,
+    ) -> SiteFuture<'a, url::Url> {
+        Box::pin(async move {
+            let task_url = url::Url::parse(&info.url)?;
+            submit(client, &task_url, language, source).await
+        })
+    }
+    fn home_url(&self) -> Option<&'static str> {
+        Some("https://atcoder.jp/home")
+    }
+    fn user_name(&self, document: &str) -> Option<String> {
+        AtCoderParser::new(document).user_screen_name()
+    }
+    // /contests/abc154/tasks/abc154_a -> /contests/abc154/submissions/me
+    fn submissions_url(&self, url: &url::Url) -> Result<(url::Url, String), anyhow::Error> {
+        let contest = url
+            .path_segments()
+            .and_then(|mut paths| paths.nth(1))
+            .ok_or_else(|| anyhow::anyhow!("Invalid problem URL: {}", url))?;
+        Ok((
+            url.join(&format!("/contests/{}/submissions/me", contest))?,
+            url.path().to_string(),
+        ))
+    }
+    fn submission_results(&self, document: &str) -> Vec<SubmissionResult> {
+        AtCoderParser::new(document).submission_results()
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -313,6 +622,178 @@ mod tests {
         equal(&samples, expecteds, url);
     }
 
+    #[test]
+    fn test_fixture() {
+        let parser = AtCoderParser::new(include_str!("../tests/fixtures/atcoder/abc163_a.html"));
+        assert_eq!(parser.problem_name(), Some("A - Circle Pond".to_string()));
+        assert_eq!(
+            parser.contest_name(),
+            Some("AtCoder Beginner Contest 163".to_string())
+        );
+        assert_eq!(parser.time_limit_ms(), Some(2000));
+        assert_eq!(parser.memory_limit_mb(), Some(1024));
+        assert_eq!(parser.float_tolerance(), Some(1e-2));
+        equal(
+            &parser.sample_cases(),
+            &[
+                ("1", "6.28318530717958623200"),
+                ("73", "458.67252742410977361942"),
+            ],
+            "abc163_a.html",
+        );
+    }
+
+    #[test]
+    fn test_submission_results() {
+        use crate::submit::Verdict;
+
+        assert_eq!(
+            AtCoderParser::new(include_str!("../tests/fixtures/atcoder/abc154_submit.html"))
+                .user_screen_name(),
+            Some("togatoga".to_string())
+        );
+        assert_eq!(
+            AtCoderParser::new(r#"<script>var userScreenName = "";</script>"#).user_screen_name(),
+            None
+        );
+        let parser = AtCoderParser::new(include_str!(
+            "../tests/fixtures/atcoder/abc154_submissions_me.html"
+        ));
+        let results = parser.submission_results();
+        assert_eq!(results.len(), 4);
+
+        assert_eq!(results[0].id, "9982004");
+        assert_eq!(results[0].problem_path, "/contests/abc154/tasks/abc154_b");
+        assert_eq!(results[0].problem_name, "B - I miss you...");
+        assert_eq!(results[0].language, "Rust (rustc 1.70.0)");
+        assert_eq!(results[0].verdict, Verdict::Judging);
+        assert_eq!(results[0].test_case, Some(3));
+        assert_eq!(results[0].total_test_cases, Some(12));
+        assert_eq!(results[0].exec_time_ms, None);
+
+        assert_eq!(results[1].verdict, Verdict::Accepted);
+        assert_eq!(results[1].exec_time_ms, Some(6));
+        assert_eq!(results[1].memory_kb, Some(2104));
+        assert_eq!(results[2].verdict, Verdict::TimeLimitExceeded);
+        assert_eq!(results[2].exec_time_ms, Some(2205));
+
+        assert_eq!(results[3].verdict, Verdict::CompilationError);
+        assert_eq!(results[3].language, "C++ 20 (gcc 12.2)");
+        assert_eq!(results[3].memory_kb, None);
+    }
+
+    #[tokio::test]
+    async fn test_submit() {
+        use crate::mock_server::{MockServer, Response};
+        use crate::submit::Language;
+
+        let server = MockServer::start(vec![
+            (
+                ("GET", "/contests/abc154/submit"),
+                Response::ok(include_str!("../tests/fixtures/atcoder/abc154_submit.html"))
+                    .header("Content-Type", "text/html; charset=utf-8"),
+            ),
+            (
+                ("POST", "/contests/abc154/submit"),
+                Response::redirect("/contests/abc154/submissions/me"),
+            ),
+            (
+                ("GET", "/contests/abc154/submissions/me"),
+                Response::ok("<html></html>"),
+            ),
+        ])
+        .await;
+        let jar = std::sync::Arc::new(crate::session::CookieJar::default());
+        jar.import_header("127.0.0.1", "REVEL_SESSION=session");
+        let client = reqwest::Client::builder()
+            .cookie_provider(jar)
+            .build()
+            .unwrap();
+        let submission_url = super::submit(
+            &client,
+            &server.url("/contests/abc154/tasks/abc154_a"),
+            &Language::Name("Rust".to_string()),
+            "fn main() {}",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            submission_url,
+            server.url("/contests/abc154/submissions/me")
+        );
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].path,
+            "/contests/abc154/submit?taskScreenName=abc154_a"
+        );
+        assert_eq!(requests[1].headers["cookie"], "REVEL_SESSION=session");
+        let form = url::form_urlencoded::parse(requests[1].body.as_bytes())
+            .into_owned()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(form["data.TaskScreenName"], "abc154_a");
+        assert_eq!(form["data.LanguageId"], "5054");
+        assert_eq!(form["sourceCode"], "fn main() {}");
+        assert_eq!(
+            form["csrf_token"],
+            "tTbXRvbhZP4PHSCxSJbXb+Xx0ZMEjBcDNTS5Wqh8IXQ="
+        );
+
+        // Not a task of the contest
+        assert!(super::submit(
+            &client,
+            &server.url("/contests/abc154/tasks/abc154_f"),
+            &Language::Name("Rust".to_string()),
+            "fn main() {}",
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_login() {
+        use crate::mock_server::{MockServer, Response};
+
+        let server = MockServer::start(vec![
+            (
+                ("GET", "/login"),
+                Response::ok(include_str!("../tests/fixtures/atcoder/abc154_submit.html")),
+            ),
+            (("POST", "/login"), Response::redirect("/home")),
+            (("GET", "/home"), Response::ok("<html></html>")),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+        super::login(&client, &server.url("/login"), "togatoga", "password")
+            .await
+            .unwrap();
+        let requests = server.requests();
+        let form = url::form_urlencoded::parse(requests[1].body.as_bytes())
+            .into_owned()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(form["username"], "togatoga");
+        assert_eq!(form["password"], "password");
+        assert_eq!(
+            form["csrf_token"],
+            "tTbXRvbhZP4PHSCxSJbXb+Xx0ZMEjBcDNTS5Wqh8IXQ="
+        );
+
+        // AtCoder shows the login page again on failure
+        let server = MockServer::start(vec![
+            (
+                ("GET", "/login"),
+                Response::ok(include_str!("../tests/fixtures/atcoder/abc154_submit.html")),
+            ),
+            (("POST", "/login"), Response::redirect("/login")),
+        ])
+        .await;
+        assert!(
+            super::login(&client, &server.url("/login"), "togatoga", "wrong")
+                .await
+                .is_err()
+        );
+    }
+
     #[tokio::test]
     async fn test_sample_cases() {
         let expecteds = vec![("2 3", "2"), ("3 4", "4"), ("3 6", "6")];
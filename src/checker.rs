@@ -0,0 +1,382 @@
+use crate::executor::{self, Executor};
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::Duration;
+
+/// How long a special judge may run on a case unless it's given by `SpecialJudge::time_limit`
+pub const DEFAULT_SPECIAL_JUDGE_TIME_LIMIT: Duration = Duration::from_secs(10);
+
+pub trait Checker {
+    /// Judges `output` of a solution against the expected `answer` for `input`.
+    /// Both are passed as they are, e.g with trailing newlines, and each checker normalizes them.
+    fn check(&self, input: &str, output: &str, answer: &str) -> Result<CheckResult, anyhow::Error>;
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CheckResult {
+    pub accepted: bool,
+    /// A comment from the checker. e.g. what a special judge printed
+    pub message: Option<String>,
+}
+
+impl CheckResult {
+    fn new(accepted: bool) -> CheckResult {
+        CheckResult {
+            accepted,
+            message: None,
+        }
+    }
+}
+
+/// How outputs of a problem are judged. This is saved in `.problem.json` as the `checker` field.
+/// e.g `{"type": "float", "tolerance": 1e-6}`, `{"type": "special", "command": "./checker"}`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CheckerConfig {
+    Exact,
+    Token,
+    YesNo,
+    UnorderedLines,
+    Float { tolerance: f64 },
+    Special { command: String },
+}
+
+impl CheckerConfig {
+    pub fn build(&self) -> Box<dyn Checker> {
+        match self {
+            CheckerConfig::Exact => Box::new(ExactChecker),
+            CheckerConfig::Token => Box::new(TokenChecker),
+            CheckerConfig::YesNo => Box::new(YesNoChecker),
+            CheckerConfig::UnorderedLines => Box::new(UnorderedLinesChecker),
+            CheckerConfig::Float { tolerance } => Box::new(FloatChecker::new(*tolerance)),
+            CheckerConfig::Special { command } => Box::new(SpecialJudge::new(command)),
+        }
+    }
+}
+
+impl std::fmt::Display for CheckerConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckerConfig::Exact => write!(f, "exact"),
+            CheckerConfig::Token => write!(f, "token"),
+            CheckerConfig::YesNo => write!(f, "yes_no"),
+            CheckerConfig::UnorderedLines => write!(f, "unordered_lines"),
+            CheckerConfig::Float { tolerance } => write!(f, "float (tolerance: {})", tolerance),
+            CheckerConfig::Special { command } => write!(f, "special judge ({})", command),
+        }
+    }
+}
+
+/// Accepts only an output which is byte-for-byte identical to the answer.
+pub struct ExactChecker;
+
+impl Checker for ExactChecker {
+    fn check(
+        &self,
+        _input: &str,
+        output: &str,
+        answer: &str,
+    ) -> Result<CheckResult, anyhow::Error> {
+        Ok(CheckResult::new(output == answer))
+    }
+}
+
+/// Compares whitespace separated tokens. Differences in spaces and line breaks are ignored.
+pub struct TokenChecker;
+
+impl Checker for TokenChecker {
+    fn check(
+        &self,
+        _input: &str,
+        output: &str,
+        answer: &str,
+    ) -> Result<CheckResult, anyhow::Error> {
+        Ok(CheckResult::new(
+            output.split_whitespace().eq(answer.split_whitespace()),
+        ))
+    }
+}
+
+/// Compares tokens ignoring ASCII case. e.g `YES`, `Yes` and `yes` are the same.
+pub struct YesNoChecker;
+
+impl Checker for YesNoChecker {
+    fn check(
+        &self,
+        _input: &str,
+        output: &str,
+        answer: &str,
+    ) -> Result<CheckResult, anyhow::Error> {
+        let outputs = output.split_whitespace().collect::<Vec<_>>();
+        let answers = answer.split_whitespace().collect::<Vec<_>>();
+        Ok(CheckResult::new(
+            outputs.len() == answers.len()
+                && outputs
+                    .iter()
+                    .zip(answers.iter())
+                    .all(|(actual, expected)| actual.eq_ignore_ascii_case(expected)),
+        ))
+    }
+}
+
+/// Accepts an output which has the same lines as the answer in any order.
+pub struct UnorderedLinesChecker;
+
+impl Checker for UnorderedLinesChecker {
+    fn check(
+        &self,
+        _input: &str,
+        output: &str,
+        answer: &str,
+    ) -> Result<CheckResult, anyhow::Error> {
+        let sorted_lines = |text: &str| {
+            let mut lines = text
+                .lines()
+                .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>();
+            lines.sort();
+            lines
+        };
+        Ok(CheckResult::new(
+            sorted_lines(output) == sorted_lines(answer),
+        ))
+    }
+}
+
+/// Compares tokens. Tokens which are both numbers are accepted if the absolute or the relative
+/// error is at most `tolerance`.
+pub struct FloatChecker {
+    tolerance: f64,
+}
+
+impl FloatChecker {
+    pub fn new(tolerance: f64) -> FloatChecker {
+        FloatChecker { tolerance }
+    }
+}
+
+impl Checker for FloatChecker {
+    fn check(
+        &self,
+        _input: &str,
+        output: &str,
+        answer: &str,
+    ) -> Result<CheckResult, anyhow::Error> {
+        let outputs = output.split_whitespace().collect::<Vec<_>>();
+        let answers = answer.split_whitespace().collect::<Vec<_>>();
+        if outputs.len() != answers.len() {
+            return Ok(CheckResult::new(false));
+        }
+        let accepted = outputs
+            .iter()
+            .zip(answers.iter())
+            .all(|(actual, expected)| {
+                if actual == expected {
+                    return true;
+                }
+                match (actual.parse::<f64>(), expected.parse::<f64>()) {
+                    (Ok(actual), Ok(expected)) if actual.is_finite() && expected.is_finite() => {
+                        let error = (actual - expected).abs();
+                        error <= self.tolerance || error <= self.tolerance * expected.abs()
+                    }
+                    _ => false,
+                }
+            });
+        Ok(CheckResult::new(accepted))
+    }
+}
+
+/// An external checker program called like testlib's `checker input output answer`.
+/// Exit code 0 means accepted.
+pub struct SpecialJudge {
+    command: String,
+    time_limit: Duration,
+}
+
+impl SpecialJudge {
+    pub fn new(command: &str) -> SpecialJudge {
+        SpecialJudge {
+            command: command.to_string(),
+            time_limit: DEFAULT_SPECIAL_JUDGE_TIME_LIMIT,
+        }
+    }
+    /// The checker is killed and the output is rejected when it runs longer than `limit`.
+    pub fn time_limit(mut self, limit: Duration) -> SpecialJudge {
+        self.time_limit = limit;
+        self
+    }
+}
+
+impl Checker for SpecialJudge {
+    fn check(&self, input: &str, output: &str, answer: &str) -> Result<CheckResult, anyhow::Error> {
+        let dir = temporary_dir()?;
+        let write = |name: &str, content: &str| -> Result<String, anyhow::Error> {
+            let path = dir.join(name);
+            std::fs::File::create(&path)?.write_all(content.as_bytes())?;
+            Ok(path.to_string_lossy().to_string())
+        };
+        let paths = [
+            write("input.txt", input)?,
+            write("output.txt", output)?,
+            write("answer.txt", answer)?,
+        ];
+
+        let commands = util::split_command(&self.command)?;
+        let program = commands
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("The special judge command is empty"))?;
+        let result = Executor::new(program)
+            .args(commands.iter().skip(1))
+            .args(paths.iter())
+            .time_limit(self.time_limit)
+            .run();
+        std::fs::remove_dir_all(&dir)?;
+        let result = result?;
+        if result.timed_out {
+            return Ok(CheckResult {
+                accepted: false,
+                message: Some(format!(
+                    "The special judge exceeded {} ms",
+                    self.time_limit.as_millis()
+                )),
+            });
+        }
+
+        // testlib reports its comment to stderr, other checkers often use stdout
+        let message = [&result.stderr, &result.stdout]
+            .iter()
+            .map(|bytes| String::from_utf8_lossy(bytes).trim().to_string())
+            .find(|message| !message.is_empty());
+        let message = if result.status.success() {
+            message
+        } else {
+            Some(message.unwrap_or_else(|| executor::status_description(&result.status)))
+        };
+        Ok(CheckResult {
+            accepted: result.status.success(),
+            message,
+        })
+    }
+}
+
+fn temporary_dir() -> Result<std::path::PathBuf, anyhow::Error> {
+    static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    let id = COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    let dir = std::env::temp_dir().join(format!("cpm-{}-{}", std::process::id(), id));
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Checker, CheckerConfig, ExactChecker, FloatChecker, TokenChecker, UnorderedLinesChecker,
+        YesNoChecker,
+    };
+    use crate::util::TempDir;
+
+    fn accepted(checker: &dyn Checker, output: &str, answer: &str) -> bool {
+        checker.check("", output, answer).unwrap().accepted
+    }
+
+    #[test]
+    fn test_exact_checker() {
+        assert!(accepted(&ExactChecker, "1 2\n", "1 2\n"));
+        assert!(!accepted(&ExactChecker, "1 2", "1 2\n"));
+    }
+
+    #[test]
+    fn test_token_checker() {
+        assert!(accepted(&TokenChecker, "Yes\n1 2 \n", "Yes\n1 2"));
+        assert!(accepted(&TokenChecker, "1\n2\n\n", "1 2"));
+        assert!(!accepted(&TokenChecker, "1\n2", "1\n"));
+        assert!(!accepted(&TokenChecker, "1", "1\n2"));
+    }
+
+    #[test]
+    fn test_yes_no_checker() {
+        assert!(accepted(&YesNoChecker, "YES\n", "Yes"));
+        assert!(!accepted(&YesNoChecker, "No", "Yes"));
+    }
+
+    #[test]
+    fn test_unordered_lines_checker() {
+        assert!(accepted(&UnorderedLinesChecker, "2 3\n1  2\n", "1 2\n2 3"));
+        assert!(!accepted(&UnorderedLinesChecker, "2 3\n", "1 2\n2 3"));
+    }
+
+    #[test]
+    fn test_float_checker() {
+        let checker = FloatChecker::new(1e-2);
+        assert!(accepted(&checker, "6.28", "6.28318530717958623200"));
+        assert!(!accepted(&checker, "6", "6.28318530717958623200"));
+        // relative error
+        let checker = FloatChecker::new(1e-6);
+        assert!(accepted(
+            &checker,
+            "1000000000000001.0\n1",
+            "1000000000000000.000000\n1"
+        ));
+        assert!(!accepted(&checker, "4.5 2", "4.5 1"));
+        assert!(!accepted(&checker, "4.5", "4.5 1"));
+        assert!(!accepted(&checker, "Yes", "No"));
+    }
+
+    #[test]
+    fn test_checker_config() {
+        let config: CheckerConfig =
+            serde_json::from_str(r#"{"type": "float", "tolerance": 1e-6}"#).unwrap();
+        assert_eq!(config, CheckerConfig::Float { tolerance: 1e-6 });
+        let config: CheckerConfig =
+            serde_json::from_str(r#"{"type": "special", "command": "./checker"}"#).unwrap();
+        assert_eq!(
+            config,
+            CheckerConfig::Special {
+                command: "./checker".to_string()
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_special_judge() {
+        // Each script gets its own file and runs through `sh`. Executing a file just written
+        // can fail with "Text file busy" while other tests spawn processes.
+        let dir = TempDir::new("checker");
+        let script = |name: &str, content: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, content).unwrap();
+            format!("sh {}", path.to_string_lossy())
+        };
+
+        // Accepts any output whose first token is the sum of the input
+        let config = CheckerConfig::Special {
+            command: script(
+                "sum.sh",
+                "read a b < \"$1\"\nread c < \"$2\"\n[ $((a + b)) -eq \"$c\" ] || { echo \"expected $((a + b))\" >&2; exit 1; }\n",
+            ),
+        };
+        let checker = config.build();
+        assert!(checker.check("1 2\n", "3\n", "").unwrap().accepted);
+        let result = checker.check("1 2\n", "4\n", "").unwrap();
+        assert!(!result.accepted);
+        assert_eq!(result.message, Some("expected 3".to_string()));
+
+        // The output and the answer are passed as they are
+        let checker = super::SpecialJudge::new(&script("raw.sh", "cmp -s \"$2\" \"$3\"\n"));
+        assert!(checker.check("", "3\n", "3\n").unwrap().accepted);
+        assert!(!checker.check("", "3\n", "3").unwrap().accepted);
+
+        // A checker which never ends
+        let checker = super::SpecialJudge::new(&script("sleep.sh", "sleep 10\n"))
+            .time_limit(std::time::Duration::from_millis(200));
+        let result = checker.check("", "3\n", "3\n").unwrap();
+        assert!(!result.accepted);
+        assert_eq!(
+            result.message,
+            Some("The special judge exceeded 200 ms".to_string())
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::parser::Parser;
+use crate::parser::{self, Parser};
+use crate::site::{self, Site, SiteFuture};
+use crate::submit::{self, Language, LanguageOption, SubmissionResult, SubmitError, Verdict};
+use crate::util::ProblemInfo;
 use easy_scraper::Pattern;
 use itertools::Itertools;
 use scraper::Selector;
@@ -15,7 +18,323 @@ impl CodeforcesParser {
     }
 }
 
+// A random string of `alphabet` for the `ftaa` and `bfaa` fields, which are a browser fingerprint
+fn random_string(len: usize, alphabet: &[u8]) -> String {
+    use std::hash::{BuildHasher, Hasher};
+    // Seeded once from the time and the process ID, then stepped with SplitMix64
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    hasher.write_u32(std::process::id());
+    let mut state = hasher.finish();
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^= z >> 31;
+            alphabet[(z % alphabet.len() as u64) as usize] as char
+        })
+        .collect()
+}
+
+/// Logs in with the form on `enter_url` (e.g `https://codeforces.com/enter`).
+pub async fn login(
+    client: &reqwest::Client,
+    enter_url: &url::Url,
+    handle_or_email: &str,
+    password: &str,
+) -> Result<(), anyhow::Error> {
+    let html = client.get(enter_url.clone()).send().await?.text().await?;
+    let csrf_token = CodeforcesParser::new(&html)
+        .csrf_token()
+        .ok_or_else(|| anyhow::anyhow!("No csrf_token in {}", enter_url))?;
+    let ftaa = random_string(18, b"abcdefghijklmnopqrstuvwxyz0123456789");
+    let bfaa = random_string(32, b"0123456789abcdef");
+    let params = [
+        ("csrf_token", csrf_token.as_str()),
+        ("action", "enter"),
+        ("ftaa", &ftaa),
+        ("bfaa", &bfaa),
+        ("handleOrEmail", handle_or_email),
+        ("password", password),
+        ("remember", "on"),
+    ];
+    let html = client
+        .post(enter_url.clone())
+        .form(&params)
+        .send()
+        .await?
+        .text()
+        .await?;
+    let parser = CodeforcesParser::new(&html);
+    if parser.is_logged_in() {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Failed to login Codeforces: {}",
+            parser
+                .login_error()
+                .unwrap_or_else(|| "unknown error".to_string())
+        ))
+    }
+}
+
+/// Submits `source` to the problem `problem_index` of the contest `contest_id` and returns the URL of
+/// "My Submissions". `base_url` is the site such as `https://codeforces.com/`.
+pub async fn submit(
+    client: &reqwest::Client,
+    base_url: &url::Url,
+    contest_id: &str,
+    problem_index: &str,
+    language: &Language,
+    source: &str,
+) -> Result<url::Url, anyhow::Error> {
+    let submit_url = base_url.join(&format!("/contest/{}/submit", contest_id))?;
+    let resp = client.get(submit_url.clone()).send().await?;
+    let page_url = resp.url().clone();
+    let parser = CodeforcesParser::new(&resp.text().await?);
+    // Codeforces redirects to /enter without a session
+    let csrf_token = match parser.csrf_token() {
+        Some(csrf_token) if page_url.path() == submit_url.path() && parser.is_logged_in() => {
+            csrf_token
+        }
+        _ => return Err(SubmitError::NotLoggedIn.into()),
+    };
+    if !parser
+        .select_options("submittedProblemIndex")
+        .iter()
+        .any(|option| option.id == problem_index)
+    {
+        return Err(anyhow::anyhow!(
+            "{} isn't a problem of the contest {}",
+            problem_index,
+            contest_id
+        ));
+    }
+    let language_id = language
+        .resolve(&parser.select_options("programTypeId"))
+        .ok_or_else(|| anyhow::anyhow!("{} isn't available on {}", language, submit_url))?;
+
+    let ftaa = random_string(18, b"abcdefghijklmnopqrstuvwxyz0123456789");
+    let bfaa = random_string(32, b"0123456789abcdef");
+    let params = [
+        ("csrf_token", csrf_token.as_str()),
+        ("ftaa", &ftaa),
+        ("bfaa", &bfaa),
+        ("action", "submitSolutionFormSubmitted"),
+        ("contestId", contest_id),
+        ("submittedProblemIndex", problem_index),
+        ("programTypeId", &language_id),
+        ("source", source),
+        ("tabSize", "4"),
+        ("sourceFile", ""),
+    ];
+    let post_url = parser
+        .submit_form_action()
+        .and_then(|action| submit_url.join(&action).ok())
+        .unwrap_or_else(|| submit_url.clone());
+    let resp = client.post(post_url).form(&params).send().await?;
+    // A successful submission is redirected to "My Submissions"
+    if resp.url().path().ends_with("/my") {
+        return Ok(resp.url().clone());
+    }
+    let parser = CodeforcesParser::new(&resp.text().await?);
+    match parser.submit_error() {
+        Some(error) if error.contains("exactly the same code") => {
+            Err(SubmitError::DuplicateSubmission.into())
+        }
+        Some(error) => Err(SubmitError::Rejected(error).into()),
+        None => Err(SubmitError::Rejected("unknown error".to_string()).into()),
+    }
+}
+
+/// The contest ID and the problem index of a problem path.
+/// e.g `/contest/4/problem/A` and `/problemset/problem/4/A` -> ("4", "A")
+pub fn contest_and_index(path: &str) -> Option<(String, String)> {
+    let paths = path
+        .split('/')
+        .filter(|path| !path.is_empty())
+        .collect::<Vec<_>>();
+    match paths.as_slice() {
+        ["contest", contest, "problem", index] | ["problemset", "problem", contest, index] => {
+            Some((contest.to_string(), index.to_string()))
+        }
+        _ => None,
+    }
+}
+
+pub struct CodeforcesSite;
+
+impl Site for CodeforcesSite {
+    fn name(&self) -> &'static str {
+        "Codeforces"
+    }
+    fn matches(&self, host: &str) -> bool {
+        host == "codeforces.com"
+    }
+    fn parser(&self, document: &str) -> Result<Box<dyn Parser>, anyhow::Error> {
+        Ok(Box::new(CodeforcesParser::new(document)))
+    }
+    // A page is a contest if it links to problems under its path
+    fn problem_url_list(&self, url: &url::Url, document: &str) -> Option<Vec<url::Url>> {
+        let url_list = CodeforcesParser::new(document).problem_url_list(url.path());
+        if url_list.is_empty() {
+            None
+        } else {
+            Some(site::join_all(url, &url_list))
+        }
+    }
+    fn contest_and_index(&self, url: &url::Url) -> Option<(String, String)> {
+        contest_and_index(url.path())
+    }
+    fn login_url(&self) -> Option<&'static str> {
+        Some("https://codeforces.com/enter")
+    }
+    fn login<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        login_url: &'a url::Url,
+        username: &'a str,
+        password: &'a str,
+    ) -> SiteFuture<'a, ()> {
+        Box::pin(login(client, login_url, username, password))
+    }
+    fn submit<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        info: &'a ProblemInfo,
+        language: &'a Language,
+        source: &'a str,
+    ) -> SiteFuture<'a, url::Url> {
+        Box::pin(async move {
+            let url = url::Url::parse(&info.url)?;
+            // Directories created before `contest_id` was saved only have the URL
+            let (contest_id, problem_index) = match (&info.contest_id, &info.problem_index) {
+                (Some(contest_id), Some(problem_index)) => {
+                    (contest_id.clone(), problem_index.clone())
+                }
+                _ => contest_and_index(url.path())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid problem URL: {}", url))?,
+            };
+            submit(client, &url, &contest_id, &problem_index, language, source).await
+        })
+    }
+    fn home_url(&self) -> Option<&'static str> {
+        Some("https://codeforces.com/")
+    }
+    fn user_name(&self, document: &str) -> Option<String> {
+        CodeforcesParser::new(document).handle()
+    }
+    // Rows of `/contest/4/my` link to `/contest/4/problem/A` even if the URL is in the problem set
+    fn submissions_url(&self, url: &url::Url) -> Result<(url::Url, String), anyhow::Error> {
+        let (contest, index) = contest_and_index(url.path())
+            .ok_or_else(|| anyhow::anyhow!("Invalid problem URL: {}", url))?;
+        Ok((
+            url.join(&format!("/contest/{}/my", contest))?,
+            format!("/contest/{}/problem/{}", contest, index),
+        ))
+    }
+    fn submission_results(&self, document: &str) -> Vec<SubmissionResult> {
+        CodeforcesParser::new(document).submission_results()
+    }
+}
+
 impl CodeforcesParser {
+    // The text of a property in the problem header without its title
+    fn property(&self, selector: &str) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse(selector).expect("invalid selector");
+        let title_selector = Selector::parse("div.property-title").expect("invalid selector");
+        document.select(&selector).next().map(|element| {
+            let title = element
+                .select(&title_selector)
+                .next()
+                .map(|title| title.text().collect::<String>())
+                .unwrap_or_default();
+            let text = element.text().collect::<String>();
+            text.replacen(&title, "", 1).trim().to_string()
+        })
+    }
+    pub fn csrf_token(&self) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse(r#"input[name="csrf_token"]"#).expect("invalid selector");
+        document
+            .select(&selector)
+            .next()
+            .and_then(|element| element.value().attr("value"))
+            .map(|token| token.to_string())
+    }
+    /// Whether the page has a logout link in its header
+    pub fn is_logged_in(&self) -> bool {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse(r#"a[href$="/logout"]"#).expect("invalid selector");
+        document.select(&selector).next().is_some()
+    }
+    /// Options of a `<select>` in a form. e.g `programTypeId` for languages
+    pub fn select_options(&self, name: &str) -> Vec<LanguageOption> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse(&format!(r#"select[name="{}"] > option"#, name))
+            .expect("invalid selector");
+        document
+            .select(&selector)
+            .filter_map(|option| {
+                let id = option.value().attr("value")?;
+                if id.is_empty() {
+                    return None;
+                }
+                Some(LanguageOption {
+                    id: id.to_string(),
+                    name: option.text().collect::<String>().trim().to_string(),
+                })
+            })
+            .collect()
+    }
+    // The form on a submit page is posted to `/contest/<id>/submit?csrf_token=...`
+    fn submit_form_action(&self) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("form.submit-form").expect("invalid selector");
+        document
+            .select(&selector)
+            .next()
+            .and_then(|form| form.value().attr("action"))
+            .map(|action| action.to_string())
+    }
+    /// An error message of the submit form. e.g `You have submitted exactly the same code before`
+    pub fn submit_error(&self) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("form.submit-form span.error").expect("invalid selector");
+        document
+            .select(&selector)
+            .map(|error| error.text().collect::<String>().trim().to_string())
+            .find(|error| !error.is_empty())
+    }
+    /// The handle in the header of a page if logged in
+    pub fn handle(&self) -> Option<String> {
+        if !self.is_logged_in() {
+            return None;
+        }
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse(r#"a[href^="/profile/"]"#).expect("invalid selector");
+        document
+            .select(&selector)
+            .next()
+            .map(|profile| profile.text().collect::<String>().trim().to_string())
+    }
+    /// An error message of the login form. e.g `Invalid handle/email or password`
+    pub fn login_error(&self) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("#enterForm span.error").expect("invalid selector");
+        document
+            .select(&selector)
+            .map(|error| error.text().collect::<String>().trim().to_string())
+            .find(|error| !error.is_empty())
+    }
     pub fn problem_url_list(&self, path: &str) -> Vec<String> {
         let document = scraper::Html::parse_document(&self.document);
         let selector = Selector::parse("a").expect("invalid selector");
@@ -31,6 +350,69 @@ impl CodeforcesParser {
         problem_url_list.dedup();
         problem_url_list
     }
+    /// Submissions on a status page such as `/contest/4/my`, newest first
+    pub fn submission_results(&self) -> Vec<SubmissionResult> {
+        let document = scraper::Html::parse_document(&self.document);
+        let row_selector = Selector::parse("table.status-frame-datatable tr[data-submission-id]")
+            .expect("invalid selector");
+        let td_selector = Selector::parse("td").expect("invalid selector");
+        let a_selector = Selector::parse("a").expect("invalid selector");
+        let verdict_selector =
+            Selector::parse("span.submissionVerdictWrapper").expect("invalid selector");
+        let test_selector =
+            Selector::parse("span.verdict-format-judged").expect("invalid selector");
+        let text =
+            |element: scraper::ElementRef| element.text().collect::<String>().trim().to_string();
+
+        document
+            .select(&row_selector)
+            .filter_map(|row| {
+                let id = row.value().attr("data-submission-id")?.to_string();
+                let tds = row.select(&td_selector).collect::<Vec<_>>();
+                // #, When, Who, Problem, Lang, Verdict, Time, Memory
+                if tds.len() < 8 {
+                    return None;
+                }
+                let problem = tds[3].select(&a_selector).next()?;
+                let verdict_wrapper = tds[5].select(&verdict_selector).next();
+                let verdict =
+                    match verdict_wrapper.and_then(|span| span.value().attr("submissionverdict")) {
+                        Some("OK") => Verdict::Accepted,
+                        Some("WRONG_ANSWER") => Verdict::WrongAnswer,
+                        Some("TIME_LIMIT_EXCEEDED") => Verdict::TimeLimitExceeded,
+                        Some("MEMORY_LIMIT_EXCEEDED") => Verdict::MemoryLimitExceeded,
+                        Some("RUNTIME_ERROR") => Verdict::RuntimeError,
+                        Some("COMPILATION_ERROR") => Verdict::CompilationError,
+                        Some("TESTING") | None => Verdict::Judging,
+                        Some(_) => Verdict::Other(text(tds[5])),
+                    };
+                let test_case = tds[5]
+                    .select(&test_selector)
+                    .next()
+                    .and_then(|test| submit::leading_number(&text(test)));
+                // The time and memory are meaningless until the judge finishes
+                let (exec_time_ms, memory_kb) = if verdict.is_final() {
+                    (
+                        submit::leading_number(&text(tds[6])),
+                        submit::leading_number(&text(tds[7])),
+                    )
+                } else {
+                    (None, None)
+                };
+                Some(SubmissionResult {
+                    id,
+                    problem_path: problem.value().attr("href")?.to_string(),
+                    problem_name: text(problem),
+                    language: text(tds[4]),
+                    verdict,
+                    test_case,
+                    total_test_cases: None,
+                    exec_time_ms,
+                    memory_kb,
+                })
+            })
+            .collect()
+    }
 }
 
 impl Parser for CodeforcesParser {
@@ -109,4 +491,284 @@ impl Parser for CodeforcesParser {
 
         inputs.into_iter().zip(outputs).collect()
     }
+    fn time_limit_ms(&self) -> Option<u64> {
+        // e.g <div class="time-limit"><div class="property-title">time limit per test</div>2 seconds</div>
+        self.property("div.time-limit")
+            .and_then(|limit| parser::parse_time_limit_ms(&limit))
+    }
+    fn memory_limit_mb(&self) -> Option<u64> {
+        self.property("div.memory-limit")
+            .and_then(|limit| parser::parse_memory_limit_mb(&limit))
+    }
+    fn float_tolerance(&self) -> Option<f64> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("div.problem-statement").expect("invalid selector");
+        document.select(&selector).next().and_then(|statement| {
+            parser::parse_float_tolerance(&statement.text().collect::<String>())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contest_and_index, random_string, CodeforcesParser};
+    use crate::parser::Parser;
+    use crate::submit::Verdict;
+
+    #[test]
+    fn test_random_string() {
+        let bfaa = random_string(32, b"0123456789abcdef");
+        assert_eq!(bfaa.len(), 32);
+        assert!(bfaa.chars().all(|c| c.is_ascii_hexdigit()));
+        // Not a single character repeated
+        let first = bfaa.chars().next().unwrap();
+        assert!(bfaa.chars().any(|c| c != first));
+    }
+
+    #[test]
+    fn test_fixture() {
+        let parser = CodeforcesParser::new(include_str!("../tests/fixtures/codeforces/4_a.html"));
+        assert_eq!(parser.problem_name(), Some("A. Watermelon".to_string()));
+        assert_eq!(
+            parser.contest_name(),
+            Some("Codeforces Beta Round 4 (Div. 2 Only)".to_string())
+        );
+        assert_eq!(parser.time_limit_ms(), Some(1000));
+        assert_eq!(parser.memory_limit_mb(), Some(64));
+        assert_eq!(parser.float_tolerance(), None);
+        assert_eq!(
+            parser.sample_cases(),
+            vec![("8\n".to_string(), "YES\n".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_submission_results() {
+        let parser = CodeforcesParser::new(include_str!("../tests/fixtures/codeforces/4_my.html"));
+        let results = parser.submission_results();
+        assert_eq!(results.len(), 4);
+
+        assert_eq!(results[0].id, "215000004");
+        assert_eq!(results[0].problem_path, "/contest/4/problem/A");
+        assert_eq!(results[0].problem_name, "A - Watermelon");
+        assert_eq!(results[0].language, "Rust 2021");
+        assert_eq!(results[0].verdict, Verdict::Judging);
+        assert_eq!(results[0].test_case, Some(5));
+        assert_eq!(results[0].exec_time_ms, None);
+
+        assert_eq!(results[1].verdict, Verdict::Accepted);
+        assert_eq!(results[1].exec_time_ms, Some(62));
+        assert_eq!(results[1].memory_kb, Some(4));
+
+        assert_eq!(results[2].verdict, Verdict::WrongAnswer);
+        assert_eq!(results[2].test_case, Some(3));
+        assert_eq!(results[3].verdict, Verdict::CompilationError);
+        assert_eq!(results[3].test_case, None);
+    }
+
+    #[test]
+    fn test_contest_and_index() {
+        let expected = Some(("4".to_string(), "A".to_string()));
+        assert_eq!(contest_and_index("/contest/4/problem/A"), expected);
+        assert_eq!(contest_and_index("/problemset/problem/4/A"), expected);
+        assert_eq!(contest_and_index("/contest/4"), None);
+    }
+
+    #[tokio::test]
+    async fn test_login() {
+        use crate::mock_server::{MockServer, Response};
+
+        let server = MockServer::start(vec![
+            (
+                ("GET", "/enter"),
+                Response::ok(include_str!("../tests/fixtures/codeforces/enter.html")),
+            ),
+            (
+                ("POST", "/enter"),
+                Response::redirect("/").header("Set-Cookie", "X-User-Sha1=abc; Path=/"),
+            ),
+            (
+                ("GET", "/"),
+                Response::ok(
+                    r#"<a href="/profile/togatoga">togatoga</a> | <a href="/2a3b4c/logout">Logout</a>"#,
+                ),
+            ),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+        super::login(&client, &server.url("/enter"), "togatoga", "password")
+            .await
+            .unwrap();
+        let html = reqwest::get(server.url("/"))
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(
+            CodeforcesParser::new(&html).handle(),
+            Some("togatoga".to_string())
+        );
+        assert_eq!(
+            CodeforcesParser::new(include_str!("../tests/fixtures/codeforces/enter.html")).handle(),
+            None
+        );
+
+        let requests = server.requests();
+        let form = url::form_urlencoded::parse(requests[1].body.as_bytes())
+            .into_owned()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(form["csrf_token"], "2f5a4c3e9d1b8a7f6e5d4c3b2a190817");
+        assert_eq!(form["action"], "enter");
+        assert_eq!(form["handleOrEmail"], "togatoga");
+        assert_eq!(form["password"], "password");
+        assert_eq!(form["ftaa"].len(), 18);
+        assert_eq!(form["bfaa"].len(), 32);
+    }
+
+    #[tokio::test]
+    async fn test_login_failure() {
+        use crate::mock_server::{MockServer, Response};
+
+        let server = MockServer::start(vec![
+            (
+                ("GET", "/enter"),
+                Response::ok(include_str!("../tests/fixtures/codeforces/enter.html")),
+            ),
+            (
+                ("POST", "/enter"),
+                Response::ok(include_str!(
+                    "../tests/fixtures/codeforces/enter_failed.html"
+                )),
+            ),
+        ])
+        .await;
+        let error = super::login(
+            &reqwest::Client::new(),
+            &server.url("/enter"),
+            "togatoga",
+            "wrong",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to login Codeforces: Invalid handle/email or password"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit() {
+        use crate::mock_server::{MockServer, Response};
+        use crate::submit::{Language, SubmitError};
+
+        let server = MockServer::start(vec![
+            (
+                ("GET", "/contest/4/submit"),
+                Response::ok(include_str!("../tests/fixtures/codeforces/4_submit.html")),
+            ),
+            (
+                ("POST", "/contest/4/submit"),
+                Response::redirect("/contest/4/my"),
+            ),
+            (("GET", "/contest/4/my"), Response::ok("<html></html>")),
+        ])
+        .await;
+        let client = reqwest::Client::new();
+        let submission_url = super::submit(
+            &client,
+            &server.base_url,
+            "4",
+            "A",
+            &Language::Name("Python 3".to_string()),
+            "print('YES')",
+        )
+        .await
+        .unwrap();
+        assert_eq!(submission_url, server.url("/contest/4/my"));
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[1].path,
+            "/contest/4/submit?csrf_token=7c1e2d3f4a5b6c7d8e9f0a1b2c3d4e5f"
+        );
+        let form = url::form_urlencoded::parse(requests[1].body.as_bytes())
+            .into_owned()
+            .collect::<std::collections::HashMap<_, _>>();
+        assert_eq!(form["csrf_token"], "7c1e2d3f4a5b6c7d8e9f0a1b2c3d4e5f");
+        assert_eq!(form["action"], "submitSolutionFormSubmitted");
+        assert_eq!(form["submittedProblemIndex"], "A");
+        assert_eq!(form["programTypeId"], "31");
+        assert_eq!(form["source"], "print('YES')");
+
+        // Not a problem of the contest
+        assert!(super::submit(
+            &client,
+            &server.base_url,
+            "4",
+            "E",
+            &Language::Id("31".to_string()),
+            "print('YES')",
+        )
+        .await
+        .is_err());
+
+        // Not logged in
+        let server = MockServer::start(vec![
+            (("GET", "/contest/4/submit"), Response::redirect("/enter")),
+            (
+                ("GET", "/enter"),
+                Response::ok(include_str!("../tests/fixtures/codeforces/enter.html")),
+            ),
+        ])
+        .await;
+        let error = super::submit(
+            &client,
+            &server.base_url,
+            "4",
+            "A",
+            &Language::Id("31".to_string()),
+            "print('YES')",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<SubmitError>(),
+            Some(&SubmitError::NotLoggedIn)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_duplicate() {
+        use crate::mock_server::{MockServer, Response};
+        use crate::submit::{Language, SubmitError};
+
+        let server = MockServer::start(vec![
+            (
+                ("GET", "/contest/4/submit"),
+                Response::ok(include_str!("../tests/fixtures/codeforces/4_submit.html")),
+            ),
+            (
+                ("POST", "/contest/4/submit"),
+                Response::ok(include_str!(
+                    "../tests/fixtures/codeforces/4_submit_duplicate.html"
+                )),
+            ),
+        ])
+        .await;
+        let error = super::submit(
+            &reqwest::Client::new(),
+            &server.base_url,
+            "4",
+            "A",
+            &Language::Name("Rust".to_string()),
+            "fn main() {}",
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<SubmitError>(),
+            Some(&SubmitError::DuplicateSubmission)
+        );
+    }
 }
@@ -0,0 +1,277 @@
+use crate::parser::{self, Parser};
+use crate::site::{self, Site, SiteFuture};
+use crate::util;
+use scraper::{ElementRef, Selector};
+
+pub struct CsesParser {
+    document: String,
+}
+
+// "Sorting and Searching", "sorting-and-searching" -> "sortingandsearching"
+fn normalize_section(section: &str) -> String {
+    section
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// The section in the fragment of a problem set URL.
+/// e.g `https://cses.fi/problemset/#sorting-and-searching` -> `sorting-and-searching`
+pub fn section(url: &url::Url) -> Option<String> {
+    let fragment = url.fragment()?;
+    let section = url::form_urlencoded::parse(format!("section={}", fragment).as_bytes())
+        .next()
+        .map(|(_, section)| section.to_string())?;
+    if section.is_empty() {
+        None
+    } else {
+        Some(section)
+    }
+}
+
+impl CsesParser {
+    pub fn new(html: &str) -> CsesParser {
+        CsesParser {
+            document: html.to_string(),
+        }
+    }
+    /// Tasks of the problem set page, e.g `/problemset/task/1068`.
+    /// `section` picks a section by its name ignoring case, spaces and hyphens. `None` is every section.
+    pub fn problem_url_list(&self, section: Option<&str>) -> Vec<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("div.content > h2, div.content > ul.task-list li.task a")
+            .expect("invalid selector");
+        let section = section.map(normalize_section);
+
+        let mut current_section = String::new();
+        let mut problem_url_list = vec![];
+        for element in document.select(&selector) {
+            if element.value().name() == "h2" {
+                current_section = normalize_section(&element.text().collect::<String>());
+                continue;
+            }
+            if section
+                .as_ref()
+                .is_some_and(|section| *section != current_section)
+            {
+                continue;
+            }
+            if let Some(url) = element.value().attr("href") {
+                if url.starts_with("/problemset/task/")
+                    && !problem_url_list.iter().any(|u| u == url)
+                {
+                    problem_url_list.push(url.to_string());
+                }
+            }
+        }
+        problem_url_list
+    }
+    // e.g <li><b>Time limit:</b> 1.00 s</li> -> "1.00 s"
+    fn constraint(&self, name: &str) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("ul.task-constraints > li").expect("invalid selector");
+        document.select(&selector).find_map(|constraint| {
+            let text = constraint.text().collect::<String>();
+            text.trim()
+                .strip_prefix(name)
+                .map(|value| value.trim().to_string())
+        })
+    }
+}
+
+impl Parser for CsesParser {
+    fn problem_name(&self) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("div.title-block > h1").expect("invalid selector");
+        document
+            .select(&selector)
+            .next()
+            .map(|title| title.text().collect::<String>().trim().to_string())
+    }
+    // The section of the task in the sidebar. e.g `Introductory Problems`
+    fn contest_name(&self) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("div.sidebar > h4").expect("invalid selector");
+        let section = document
+            .select(&selector)
+            .next()
+            .map(|section| section.text().collect::<String>().trim().to_string())
+            .unwrap_or_else(|| "CSES Problem Set".to_string());
+        Some(section)
+    }
+    fn sample_cases(&self) -> Vec<(String, String)> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("div.md").expect("invalid selector");
+        let statement = match document.select(&selector).next() {
+            Some(statement) => statement,
+            None => return vec![],
+        };
+        // <pre>s under headings like "Example" or "Example 2" are an input and an output in turn
+        let mut in_example = false;
+        let mut pres = vec![];
+        for element in statement.children().filter_map(ElementRef::wrap) {
+            match element.value().name() {
+                "h1" | "h2" | "h3" | "h4" => {
+                    in_example = element
+                        .text()
+                        .collect::<String>()
+                        .trim()
+                        .starts_with("Example");
+                }
+                "pre" if in_example => pres.push(element.text().collect::<String>()),
+                _ => {}
+            }
+        }
+        util::pair_samples(pres)
+    }
+    fn time_limit_ms(&self) -> Option<u64> {
+        self.constraint("Time limit:")
+            .and_then(|limit| parser::parse_time_limit_ms(&limit))
+    }
+    fn memory_limit_mb(&self) -> Option<u64> {
+        self.constraint("Memory limit:")
+            .and_then(|limit| parser::parse_memory_limit_mb(&limit))
+    }
+    fn float_tolerance(&self) -> Option<f64> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("div.md").expect("invalid selector");
+        document.select(&selector).next().and_then(|statement| {
+            parser::parse_float_tolerance(&statement.text().collect::<String>())
+        })
+    }
+}
+
+pub struct CsesSite;
+
+impl Site for CsesSite {
+    fn name(&self) -> &'static str {
+        "CSES"
+    }
+    fn matches(&self, host: &str) -> bool {
+        host == "cses.fi"
+    }
+    fn parser(&self, document: &str) -> Result<Box<dyn Parser>, anyhow::Error> {
+        Ok(Box::new(CsesParser::new(document)))
+    }
+    // `/problemset/task/1068` -> ("problemset", "1068")
+    fn contest_and_index(&self, url: &url::Url) -> Option<(String, String)> {
+        match url.path_segments()?.collect::<Vec<_>>().as_slice() {
+            ["problemset", "task", task] => Some(("problemset".to_string(), task.to_string())),
+            _ => None,
+        }
+    }
+    // The problem set has hundreds of tasks, so `get` takes one section at a time
+    fn fetch_document<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        url: &'a url::Url,
+    ) -> SiteFuture<'a, String> {
+        Box::pin(async move {
+            if !url.path().starts_with("/problemset/task/") && section(url).is_none() {
+                return Err(anyhow::anyhow!(
+                    "Choose a section of the problem set with --section (e.g --section \"Introductory Problems\") or at the end of the URL (e.g https://cses.fi/problemset/#introductory-problems)"
+                ));
+            }
+            Ok(client.get(url.clone()).send().await?.text().await?)
+        })
+    }
+    // Every task of the section in the fragment. e.g https://cses.fi/problemset/#sorting-and-searching
+    fn problem_url_list(&self, url: &url::Url, document: &str) -> Option<Vec<url::Url>> {
+        if url.path().starts_with("/problemset/task/") {
+            return None;
+        }
+        let url_list = CsesParser::new(document).problem_url_list(section(url).as_deref());
+        Some(site::join_all(url, &url_list))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{section, CsesParser, CsesSite};
+    use crate::parser::Parser;
+    use crate::site::Site;
+
+    #[test]
+    fn test_fixture() {
+        let parser = CsesParser::new(include_str!("../tests/fixtures/cses/1068.html"));
+        assert_eq!(parser.problem_name(), Some("Weird Algorithm".to_string()));
+        assert_eq!(
+            parser.contest_name(),
+            Some("Introductory Problems".to_string())
+        );
+        assert_eq!(parser.time_limit_ms(), Some(1000));
+        assert_eq!(parser.memory_limit_mb(), Some(512));
+        assert_eq!(parser.float_tolerance(), None);
+        assert_eq!(
+            parser.sample_cases(),
+            vec![("3\n".to_string(), "3 10 5 16 8 4 2 1\n".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_multiple_examples() {
+        let parser = CsesParser::new(
+            r#"<div class="md"><h1>Input</h1><pre>n</pre>
+            <h2>Example 1</h2><p>Input:</p><pre>1
+</pre><p>Output:</p><pre>2
+</pre><h2>Example 2</h2><p>Input:</p><pre>3
+</pre><p>Output:</p><pre>4
+</pre></div>"#,
+        );
+        assert_eq!(
+            parser.sample_cases(),
+            vec![
+                ("1\n".to_string(), "2\n".to_string()),
+                ("3\n".to_string(), "4\n".to_string())
+            ]
+        );
+        assert_eq!(parser.contest_name(), Some("CSES Problem Set".to_string()));
+    }
+
+    #[test]
+    fn test_problem_url_list() {
+        let parser = CsesParser::new(include_str!("../tests/fixtures/cses/problemset.html"));
+        assert_eq!(
+            parser.problem_url_list(None),
+            vec![
+                "/problemset/task/1068",
+                "/problemset/task/1083",
+                "/problemset/task/1069",
+                "/problemset/task/1621",
+                "/problemset/task/1084"
+            ]
+        );
+        assert_eq!(
+            parser.problem_url_list(Some("sorting-and-searching")),
+            vec!["/problemset/task/1621", "/problemset/task/1084"]
+        );
+        assert!(parser.problem_url_list(Some("graph")).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_whole_problem_set() {
+        // Fails before sending a request
+        let client = reqwest::Client::new();
+        let url = url::Url::parse("https://cses.fi/problemset/").unwrap();
+        assert!(CsesSite.fetch_document(&client, &url).await.is_err());
+    }
+
+    #[test]
+    fn test_section() {
+        let section = |url: &str| section(&url::Url::parse(url).unwrap());
+        assert_eq!(
+            section("https://cses.fi/problemset/#Sorting%20and%20Searching"),
+            Some("Sorting and Searching".to_string())
+        );
+        assert_eq!(section("https://cses.fi/problemset/"), None);
+
+        // `get --section`
+        let mut url = url::Url::parse("https://cses.fi/problemset/").unwrap();
+        url.set_fragment(Some("Sorting and Searching"));
+        assert_eq!(
+            super::section(&url),
+            Some("Sorting and Searching".to_string())
+        );
+    }
+}
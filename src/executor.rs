@@ -1 +1,524 @@
+use std::io::{Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+/// Runs a single command with a given stdin and collects everything a judge needs to know
+/// about the run.
+///
+/// ```no_run
+/// use cpm::executor::Executor;
+/// use std::time::Duration;
+///
+/// let result = Executor::new("./a.out")
+///     .stdin(b"1 2\n")
+///     .time_limit(Duration::from_secs(2))
+///     .run()
+///     .unwrap();
+/// println!("{}", String::from_utf8_lossy(&result.stdout));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Executor {
+    program: String,
+    args: Vec<String>,
+    stdin: Vec<u8>,
+    time_limit: Option<Duration>,
+    memory_limit: Option<u64>,
+}
+
+#[derive(Debug)]
+pub struct ExecutionResult {
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub status: ExitStatus,
+    pub wall_time: Duration,
+    /// User + system time. `None` on platforms where it can't be measured.
+    pub cpu_time: Option<Duration>,
+    /// Peak resident set size in bytes. `None` on platforms where it can't be measured.
+    pub peak_memory: Option<u64>,
+    /// The process was killed because it ran longer than the time limit.
+    pub timed_out: bool,
+}
+
+impl Executor {
+    pub fn new(program: &str) -> Executor {
+        Executor {
+            program: program.to_string(),
+            args: vec![],
+            stdin: vec![],
+            time_limit: None,
+            memory_limit: None,
+        }
+    }
+    pub fn arg(mut self, arg: &str) -> Executor {
+        self.args.push(arg.to_string());
+        self
+    }
+    pub fn args<I, S>(mut self, args: I) -> Executor
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.args
+            .extend(args.into_iter().map(|arg| arg.as_ref().to_string()));
+        self
+    }
+    pub fn stdin(mut self, input: &[u8]) -> Executor {
+        self.stdin = input.to_vec();
+        self
+    }
+    /// Wall clock limit. The process (and its process group on Unix) is killed when it's exceeded.
+    pub fn time_limit(mut self, limit: Duration) -> Executor {
+        self.time_limit = Some(limit);
+        self
+    }
+    /// Caps the address space of the process in bytes with `RLIMIT_AS`.
+    /// Allocations beyond it fail inside the process. It has no effect on non-Unix platforms.
+    pub fn memory_limit(mut self, bytes: u64) -> Executor {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            // Put the child in its own process group so that a timeout also kills
+            // whatever it spawned (e.g. `sh -c` or `cargo run`).
+            command.process_group(0);
+            if let Some(limit) = self.memory_limit {
+                let limit = limit as libc::rlim_t;
+                unsafe {
+                    command.pre_exec(move || {
+                        let rlimit = libc::rlimit {
+                            rlim_cur: limit,
+                            rlim_max: limit,
+                        };
+                        if libc::setrlimit(libc::RLIMIT_AS, &rlimit) != 0 {
+                            return Err(std::io::Error::last_os_error());
+                        }
+                        Ok(())
+                    });
+                }
+            }
+        }
+        command
+    }
+
+    pub fn run(&self) -> Result<ExecutionResult, anyhow::Error> {
+        let mut command = self.command();
+        let start = Instant::now();
+        let mut child = command.spawn()?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = self.stdin.clone();
+        // The child may exit without reading all of its input. A broken pipe is not our error.
+        let stdin_thread = std::thread::spawn(move || {
+            let _ = stdin.write_all(&input);
+        });
+        let stdout_thread = spawn_reader(child.stdout.take().expect("stdout is piped"));
+        let stderr_thread = spawn_reader(child.stderr.take().expect("stderr is piped"));
+
+        let exit = wait(&mut child, start, self.time_limit);
+        if exit.is_err() {
+            // The readers only finish when every process holding the pipes has gone
+            kill_and_reap(&mut child);
+        }
+        let wall_time = start.elapsed();
+
+        let _ = stdin_thread.join();
+        let stdout = stdout_thread.join().expect("stdout reader panicked");
+        let stderr = stderr_thread.join().expect("stderr reader panicked");
+        let (status, usage, timed_out) = exit?;
+        let (stdout, stderr) = (stdout?, stderr?);
+
+        Ok(ExecutionResult {
+            stdout,
+            stderr,
+            status,
+            wall_time,
+            cpu_time: usage.map(|usage| usage.cpu_time),
+            peak_memory: usage.map(|usage| usage.peak_memory),
+            timed_out,
+        })
+    }
+}
+
+/// Which side wrote a part of an interactive transcript
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    SolutionToInteractor,
+    InteractorToSolution,
+}
+
+#[derive(Debug)]
+pub struct InteractiveResult {
+    /// `stdout` is always empty because it's consumed by the interactor
+    pub solution: ExecutionResult,
+    /// `stdout` is always empty because it's consumed by the solution
+    pub interactor: ExecutionResult,
+    /// Everything the two processes sent to each other in order
+    pub transcript: Vec<(Direction, String)>,
+}
+
+/// Runs a solution and an interactor connecting the stdout of each to the stdin of the other.
+/// The stdin given to the executors is ignored.
+pub fn run_interactive(
+    solution: &Executor,
+    interactor: &Executor,
+) -> Result<InteractiveResult, anyhow::Error> {
+    let start = Instant::now();
+    let mut solution_child = solution.command().spawn()?;
+    let mut interactor_child = match interactor.command().spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            let _ = solution_child.kill();
+            let _ = solution_child.wait();
+            return Err(err.into());
+        }
+    };
+
+    let transcript = Arc::new(Mutex::new(vec![]));
+    let relays = vec![
+        spawn_relay(
+            solution_child.stdout.take().expect("stdout is piped"),
+            interactor_child.stdin.take().expect("stdin is piped"),
+            Direction::SolutionToInteractor,
+            transcript.clone(),
+        ),
+        spawn_relay(
+            interactor_child.stdout.take().expect("stdout is piped"),
+            solution_child.stdin.take().expect("stdin is piped"),
+            Direction::InteractorToSolution,
+            transcript.clone(),
+        ),
+    ];
+    let solution_stderr = spawn_reader(solution_child.stderr.take().expect("stderr is piped"));
+    let interactor_stderr = spawn_reader(interactor_child.stderr.take().expect("stderr is piped"));
+
+    // Both processes are waited for at the same time so that either of them can hit its time limit
+    let spawn_waiter = |mut child: Child, time_limit: Option<Duration>| {
+        std::thread::spawn(move || {
+            let exit = wait(&mut child, start, time_limit);
+            if exit.is_err() {
+                kill_and_reap(&mut child);
+            }
+            exit.map(|(status, usage, timed_out)| (status, usage, timed_out, start.elapsed()))
+        })
+    };
+    let solution_waiter = spawn_waiter(solution_child, solution.time_limit);
+    let interactor_waiter = spawn_waiter(interactor_child, interactor.time_limit);
+    let solution_exit = solution_waiter.join().expect("waiter panicked");
+    let interactor_exit = interactor_waiter.join().expect("waiter panicked");
+
+    for relay in relays {
+        relay.join().expect("relay panicked");
+    }
+    let to_result =
+        |exit: Result<(ExitStatus, Option<ResourceUsage>, bool, Duration), anyhow::Error>,
+         stderr: std::thread::JoinHandle<std::io::Result<Vec<u8>>>|
+         -> Result<ExecutionResult, anyhow::Error> {
+            let (status, usage, timed_out, wall_time) = exit?;
+            Ok(ExecutionResult {
+                stdout: vec![],
+                stderr: stderr.join().expect("stderr reader panicked")?,
+                status,
+                wall_time,
+                cpu_time: usage.map(|usage| usage.cpu_time),
+                peak_memory: usage.map(|usage| usage.peak_memory),
+                timed_out,
+            })
+        };
+    let solution = to_result(solution_exit, solution_stderr)?;
+    let interactor = to_result(interactor_exit, interactor_stderr)?;
+    let transcript = std::mem::take(&mut *transcript.lock().expect("poisoned transcript"));
+    Ok(InteractiveResult {
+        solution,
+        interactor,
+        transcript,
+    })
+}
+
+// Copies everything from `reader` to `writer` as soon as it arrives and records it.
+fn spawn_relay<R: Read + Send + 'static, W: Write + Send + 'static>(
+    mut reader: R,
+    writer: W,
+    direction: Direction,
+    transcript: Arc<Mutex<Vec<(Direction, String)>>>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut writer = Some(writer);
+        let mut buf = [0u8; 8192];
+        loop {
+            let len = match reader.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(len) => len,
+            };
+            let chunk = String::from_utf8_lossy(&buf[..len]).to_string();
+            {
+                let mut transcript = transcript.lock().expect("poisoned transcript");
+                match transcript.last_mut() {
+                    Some((last_direction, text))
+                        if *last_direction == direction && !text.ends_with('\n') =>
+                    {
+                        text.push_str(&chunk)
+                    }
+                    _ => transcript.push((direction, chunk)),
+                }
+            }
+            // Keep draining after the receiver has gone away so that the sender never blocks on a full pipe
+            if let Some(w) = writer.as_mut() {
+                if w.write_all(&buf[..len]).and_then(|_| w.flush()).is_err() {
+                    writer = None;
+                }
+            }
+        }
+        // Dropping the writer closes the pipe and the receiver sees EOF
+    })
+}
+
+/// A human readable description of how the process ended. e.g. `exit code 1`, `signal 11 (SIGSEGV)`
+pub fn status_description(status: &ExitStatus) -> String {
+    if let Some(code) = status.code() {
+        return format!("exit code {}", code);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return match signal_name(signal) {
+                Some(name) => format!("signal {} ({})", signal, name),
+                None => format!("signal {}", signal),
+            };
+        }
+    }
+    status.to_string()
+}
+
+#[cfg(unix)]
+fn signal_name(signal: i32) -> Option<&'static str> {
+    let name = match signal {
+        libc::SIGHUP => "SIGHUP",
+        libc::SIGINT => "SIGINT",
+        libc::SIGQUIT => "SIGQUIT",
+        libc::SIGILL => "SIGILL",
+        libc::SIGTRAP => "SIGTRAP",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGPIPE => "SIGPIPE",
+        libc::SIGALRM => "SIGALRM",
+        libc::SIGTERM => "SIGTERM",
+        libc::SIGXCPU => "SIGXCPU",
+        libc::SIGXFSZ => "SIGXFSZ",
+        _ => return None,
+    };
+    Some(name)
+}
+
+fn spawn_reader<R: Read + Send + 'static>(
+    mut reader: R,
+) -> std::thread::JoinHandle<std::io::Result<Vec<u8>>> {
+    std::thread::spawn(move || {
+        let mut buf = vec![];
+        reader.read_to_end(&mut buf)?;
+        Ok(buf)
+    })
+}
+
+#[derive(Clone, Copy)]
+struct ResourceUsage {
+    cpu_time: Duration,
+    peak_memory: u64,
+}
+
+// Kills the process (and its process group on Unix) after `wait` failed and reaps it
+fn kill_and_reap(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(unix)]
+fn wait(
+    child: &mut Child,
+    start: Instant,
+    time_limit: Option<Duration>,
+) -> Result<(ExitStatus, Option<ResourceUsage>, bool), anyhow::Error> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = child.id() as libc::pid_t;
+    let mut timed_out = false;
+    loop {
+        let mut status = 0;
+        let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+        // Once the child has been killed there is nothing left to poll for.
+        let options = if timed_out { 0 } else { libc::WNOHANG };
+        let ret = unsafe { libc::wait4(pid, &mut status, options, &mut rusage) };
+        if ret == -1 {
+            let err = std::io::Error::last_os_error();
+            if err.kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(err.into());
+        }
+        if ret == pid {
+            let to_duration = |time: libc::timeval| {
+                Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+            };
+            // ru_maxrss is in kilobytes on Linux and in bytes on macOS.
+            let peak_memory = if cfg!(target_os = "macos") {
+                rusage.ru_maxrss as u64
+            } else {
+                rusage.ru_maxrss as u64 * 1024
+            };
+            let usage = ResourceUsage {
+                cpu_time: to_duration(rusage.ru_utime) + to_duration(rusage.ru_stime),
+                peak_memory,
+            };
+            return Ok((ExitStatus::from_raw(status), Some(usage), timed_out));
+        }
+        if time_limit.is_some_and(|limit| start.elapsed() > limit) {
+            unsafe {
+                libc::kill(-pid, libc::SIGKILL);
+            }
+            timed_out = true;
+            continue;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[cfg(not(unix))]
+fn wait(
+    child: &mut Child,
+    start: Instant,
+    time_limit: Option<Duration>,
+) -> Result<(ExitStatus, Option<ResourceUsage>, bool), anyhow::Error> {
+    let mut timed_out = false;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok((status, None, timed_out));
+        }
+        if !timed_out && time_limit.is_some_and(|limit| start.elapsed() > limit) {
+            child.kill()?;
+            timed_out = true;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::Executor;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stdin_and_stdout() {
+        let result = Executor::new("cat").stdin(b"1 2\n3\n").run().unwrap();
+        assert!(result.status.success());
+        assert_eq!(result.stdout, b"1 2\n3\n");
+        assert!(result.stderr.is_empty());
+        assert!(!result.timed_out);
+        assert!(result.cpu_time.is_some());
+        assert!(result.peak_memory.unwrap() > 0);
+    }
+
+    #[test]
+    fn test_exit_status_and_stderr() {
+        let result = Executor::new("sh")
+            .args(["-c", "echo oops >&2; exit 3"])
+            .run()
+            .unwrap();
+        assert_eq!(result.status.code(), Some(3));
+        assert_eq!(result.stderr, b"oops\n");
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let result = Executor::new("sh")
+            .args(["-c", "sleep 10"])
+            .time_limit(Duration::from_millis(100))
+            .run()
+            .unwrap();
+        assert!(result.timed_out);
+        assert!(!result.status.success());
+        assert!(result.wall_time < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_interactive() {
+        // Guess a number in [1, 100] by binary search
+        let solution = Executor::new("sh").args([
+            "-c",
+            "lo=1; hi=100; while :; do mid=$(((lo + hi) / 2)); echo $mid; read r; case $r in \
+             correct) exit 0;; higher) lo=$((mid + 1));; lower) hi=$((mid - 1));; esac; done",
+        ]);
+        let interactor = Executor::new("sh").args([
+            "-c",
+            "n=0; while read g; do n=$((n + 1)); \
+             if [ $g -eq 42 ]; then echo correct; exit 0; \
+             elif [ $g -lt 42 ]; then echo higher; else echo lower; fi; done; exit 1",
+        ]);
+        let result = super::run_interactive(&solution, &interactor).unwrap();
+        assert!(result.solution.status.success());
+        assert!(result.interactor.status.success());
+        assert_eq!(
+            result.transcript.first(),
+            Some(&(super::Direction::SolutionToInteractor, "50\n".to_string()))
+        );
+        assert_eq!(
+            result.transcript.last(),
+            Some(&(
+                super::Direction::InteractorToSolution,
+                "correct\n".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_interactive_solution_exits_early() {
+        // The interactor must not wait forever for a solution that has gone
+        let solution = Executor::new("true");
+        let interactor = Executor::new("sh")
+            .args(["-c", "read g || exit 1"])
+            .time_limit(Duration::from_secs(5));
+        let result = super::run_interactive(&solution, &interactor).unwrap();
+        assert!(!result.interactor.timed_out);
+        assert_eq!(result.interactor.status.code(), Some(1));
+    }
+
+    #[test]
+    fn test_signal() {
+        let result = Executor::new("sh")
+            .args(["-c", "kill -SEGV $$"])
+            .run()
+            .unwrap();
+        assert_eq!(
+            super::status_description(&result.status),
+            "signal 11 (SIGSEGV)"
+        );
+    }
+
+    #[test]
+    fn test_memory_limit() {
+        // 64 MB isn't enough for allocating 256 MB
+        let result = Executor::new("sh")
+            .args(["-c", "x=$(head -c 268435456 /dev/zero | tr '\\0' a)"])
+            .memory_limit(64 * 1024 * 1024)
+            .run()
+            .unwrap();
+        assert!(!result.status.success());
+    }
+}
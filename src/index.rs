@@ -0,0 +1,310 @@
+//! An index of the problem directories under the root path, so that `cpm list` doesn't have to
+//! walk the whole tree.
+
+use crate::site;
+use crate::util::{self, ProblemInfo, SolveStatus};
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct WorkspaceIndex {
+    /// The root path this index was built from
+    pub root: String,
+    #[serde(default, with = "ts_seconds_option")]
+    pub indexed_at: Option<DateTime<Utc>>,
+    /// `.problem.json` keyed by its canonicalized directory
+    pub problems: BTreeMap<String, ProblemInfo>,
+    /// Directories with a `.problem` or `.problem.json` of an old format
+    #[serde(default)]
+    pub legacy_dirs: BTreeSet<String>,
+}
+
+/// Conditions of `WorkspaceIndex::query`. `None` matches everything.
+#[derive(Default, Clone)]
+pub struct IndexQuery {
+    /// e.g `atcoder.jp`
+    pub host: Option<String>,
+    /// A contest ID in the URL (e.g `abc154`, `4`) or a contest name, ignoring case.
+    /// `*` and `?` are wildcards. e.g `abc15*`
+    pub contest: Option<String>,
+    pub created_since: Option<DateTime<Utc>>,
+    pub created_until: Option<DateTime<Utc>>,
+    pub status: Option<SolveStatus>,
+}
+
+/// The contest ID in a problem URL, given by its site.
+/// e.g `https://atcoder.jp/contests/abc154/tasks/abc154_a` -> `abc154`,
+/// `https://codeforces.com/contest/4/problem/A` -> `4`
+pub fn contest_id(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    site::find(&url)?
+        .contest_and_index(&url)
+        .map(|(contest_id, _)| contest_id)
+}
+
+/// Matches `text` with a pattern of `*` (any string) and `?` (any character), ignoring case
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase().chars().collect::<Vec<_>>();
+    let text = text.to_lowercase().chars().collect::<Vec<_>>();
+    // Backtracks to the last `*` on a mismatch
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+impl IndexQuery {
+    pub fn matches(&self, info: &ProblemInfo) -> bool {
+        if let Some(host) = &self.host {
+            let url = url::Url::parse(&info.url).ok();
+            if url.as_ref().and_then(|url| url.host_str()) != Some(host.as_str()) {
+                return false;
+            }
+        }
+        if let Some(contest) = &self.contest {
+            let contest = contest.trim();
+            let id_matches = contest_id(&info.url).is_some_and(|id| glob_match(contest, &id));
+            if !id_matches && !glob_match(contest, info.contest_name.trim()) {
+                return false;
+            }
+        }
+        if self.created_since.is_some() || self.created_until.is_some() {
+            // An old `.problem.json` may not have `created_at`
+            let created_at = match info.created_at {
+                Some(created_at) => created_at,
+                None => return false,
+            };
+            if self.created_since.is_some_and(|since| created_at < since)
+                || self.created_until.is_some_and(|until| created_at > until)
+            {
+                return false;
+            }
+        }
+        if self
+            .status
+            .is_some_and(|status| info.solve_status() != status)
+        {
+            return false;
+        }
+        true
+    }
+}
+
+impl WorkspaceIndex {
+    /// Walks `root` and reads every `.problem.json`.
+    pub fn rebuild(root: &Path) -> WorkspaceIndex {
+        let mut index = WorkspaceIndex {
+            root: root.to_string_lossy().to_string(),
+            indexed_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        for entry in walkdir::WalkDir::new(root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|entry| entry.file_name() == ".problem" || entry.file_name() == ".problem.json")
+        {
+            let dir = match entry.path().parent() {
+                Some(dir) => dir,
+                None => continue,
+            };
+            match util::load_problem_info(dir) {
+                Ok(info) => index.upsert(dir, info),
+                Err(_) => {
+                    index.legacy_dirs.insert(key(dir));
+                }
+            }
+        }
+        index
+    }
+    /// Loads an index from `path`. A missing file is `None`.
+    pub fn load(path: &Path) -> Result<Option<WorkspaceIndex>, anyhow::Error> {
+        util::load_json(path)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), anyhow::Error> {
+        util::save_json(path, self)
+    }
+    pub fn upsert(&mut self, dir: &Path, info: ProblemInfo) {
+        let dir = key(dir);
+        self.legacy_dirs.remove(&dir);
+        self.problems.insert(dir, info);
+    }
+    pub fn remove(&mut self, dir: &Path) {
+        let dir = key(dir);
+        self.legacy_dirs.remove(&dir);
+        self.problems.remove(&dir);
+    }
+    /// Problems matching `query` with their directories, sorted by the directory
+    pub fn query<'a>(
+        &'a self,
+        query: &'a IndexQuery,
+    ) -> impl Iterator<Item = (&'a str, &'a ProblemInfo)> + 'a {
+        self.problems
+            .iter()
+            .filter(move |(_, info)| query.matches(info))
+            .map(|(dir, info)| (dir.as_str(), info))
+    }
+}
+
+// The key of `dir`. It's canonicalized so that a relative or symlinked root and the current
+// directory of `cpm test` give the same key. A removed directory is kept as it is.
+fn key(dir: &Path) -> String {
+    dir.canonicalize()
+        .unwrap_or_else(|_| dir.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// $HOME/.cpm/index.json
+pub fn index_path() -> PathBuf {
+    dirs::home_dir().unwrap().join(".cpm").join("index.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{contest_id, glob_match, IndexQuery, WorkspaceIndex};
+    use crate::util::{self, problem_info, SolveStatus, TempDir};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_contest_id() {
+        assert_eq!(
+            contest_id("https://atcoder.jp/contests/abc154/tasks/abc154_a"),
+            Some("abc154".to_string())
+        );
+        assert_eq!(
+            contest_id("https://codeforces.com/problemset/problem/4/A"),
+            Some("4".to_string())
+        );
+        assert_eq!(contest_id("https://example.com/"), None);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("abc154", "ABC154"));
+        assert!(glob_match("abc15*", "abc154"));
+        assert!(glob_match("abc15?", "abc154"));
+        assert!(glob_match("*beginner*", "AtCoder Beginner Contest 154"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("abc15?", "abc1545"));
+        assert!(!glob_match("arc*", "abc154"));
+        assert!(!glob_match("abc", "abc154"));
+    }
+
+    #[test]
+    fn test_rebuild_and_query() {
+        let temp_dir = TempDir::new("index");
+        let root = temp_dir.path();
+        let abc154_a = root.join("atcoder.jp/contests/abc154/tasks/abc154_a");
+        let cf_4_a = root.join("codeforces.com/contest/4/problem/A");
+        let legacy = root.join("atcoder.jp/contests/abc001/tasks/abc001_1");
+        for dir in [&abc154_a, &cf_4_a, &legacy] {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        util::create_problem_info_json(
+            problem_info(
+                "https://atcoder.jp/contests/abc154/tasks/abc154_a",
+                "AtCoder Beginner Contest 154",
+                "problem",
+                Some(1581249600),
+            ),
+            &abc154_a,
+        )
+        .unwrap();
+        util::create_problem_info_json(
+            problem_info(
+                "https://codeforces.com/contest/4/problem/A",
+                "Codeforces Beta Round 4 (Div. 2 Only)",
+                "problem",
+                Some(1690000000),
+            ),
+            &cf_4_a,
+        )
+        .unwrap();
+        std::fs::write(legacy.join(".problem"), "url").unwrap();
+
+        // A path through `.` gives the same keys as `cpm get` and `cpm test`
+        let mut index = WorkspaceIndex::rebuild(&root.join("."));
+        assert_eq!(index.problems.len(), 2);
+        assert_eq!(index.legacy_dirs.len(), 1);
+        let abc154_a_info = index.problems[abc154_a.to_str().unwrap()].clone();
+        index.upsert(&abc154_a, abc154_a_info);
+        assert_eq!(index.problems.len(), 2);
+
+        let dirs = |query: &IndexQuery| {
+            index
+                .query(query)
+                .map(|(dir, _)| dir.to_string())
+                .collect::<Vec<_>>()
+        };
+        let abc154_a = abc154_a.to_string_lossy().to_string();
+        let cf_4_a = cf_4_a.to_string_lossy().to_string();
+        assert_eq!(
+            dirs(&IndexQuery::default()),
+            vec![abc154_a.clone(), cf_4_a.clone()]
+        );
+        let query = IndexQuery {
+            host: Some("codeforces.com".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(dirs(&query), vec![cf_4_a.clone()]);
+        let query = IndexQuery {
+            contest: Some("ABC154".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(dirs(&query), vec![abc154_a.clone()]);
+        let query = IndexQuery {
+            contest: Some("codeforces beta round 4 (div. 2 only)".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(dirs(&query), vec![cf_4_a.clone()]);
+        let query = IndexQuery {
+            contest: Some("*Round 4*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(dirs(&query), vec![cf_4_a.clone()]);
+        let query = IndexQuery {
+            created_since: Utc.timestamp_opt(1600000000, 0).single(),
+            ..Default::default()
+        };
+        assert_eq!(dirs(&query), vec![cf_4_a]);
+        let query = IndexQuery {
+            created_until: Utc.timestamp_opt(1600000000, 0).single(),
+            ..Default::default()
+        };
+        assert_eq!(dirs(&query), vec![abc154_a.clone()]);
+        let query = IndexQuery {
+            status: Some(SolveStatus::Unsolved),
+            ..Default::default()
+        };
+        assert_eq!(dirs(&query).len(), 2);
+        let query = IndexQuery {
+            status: Some(SolveStatus::Solved),
+            ..Default::default()
+        };
+        assert!(dirs(&query).is_empty());
+
+        let path = root.join("index.json");
+        index.save(&path).unwrap();
+        let loaded = WorkspaceIndex::load(&path).unwrap().unwrap();
+        assert_eq!(loaded.problems.len(), 2);
+        assert_eq!(loaded.root, index.root);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::parser::{self, Parser};
+use crate::site::Site;
+use crate::util;
+use scraper::Selector;
+
+pub struct KattisParser {
+    document: String,
+}
+
+impl KattisParser {
+    pub fn new(html: &str) -> KattisParser {
+        KattisParser {
+            document: html.to_string(),
+        }
+    }
+    // The value after a label in the sidebar. e.g "1 second" for "CPU Time limit"
+    fn metadata(&self, label: &str) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let texts = document
+            .root_element()
+            .text()
+            .map(|text| text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>();
+        let position = texts
+            .iter()
+            .position(|text| text.eq_ignore_ascii_case(label))?;
+        texts.get(position + 1).map(|value| value.to_string())
+    }
+}
+
+impl Parser for KattisParser {
+    fn problem_name(&self) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("h1").expect("invalid selector");
+        document
+            .select(&selector)
+            .next()
+            .map(|title| title.text().collect::<String>().trim().to_string())
+    }
+    // Problems on Kattis don't belong to a contest
+    fn contest_name(&self) -> Option<String> {
+        Some("Kattis".to_string())
+    }
+    fn sample_cases(&self) -> Vec<(String, String)> {
+        let document = scraper::Html::parse_document(&self.document);
+        let sample_selector = Selector::parse("table.sample").expect("invalid selector");
+        let pre_selector = Selector::parse("pre").expect("invalid selector");
+        document
+            .select(&sample_selector)
+            .filter_map(|sample| {
+                let pres = sample
+                    .select(&pre_selector)
+                    .map(|pre| pre.text().collect::<String>())
+                    .take(2)
+                    .collect();
+                util::pair_samples(pres).pop()
+            })
+            .collect()
+    }
+    fn time_limit_ms(&self) -> Option<u64> {
+        self.metadata("CPU Time limit")
+            .and_then(|limit| parser::parse_time_limit_ms(&limit))
+    }
+    fn memory_limit_mb(&self) -> Option<u64> {
+        self.metadata("Memory limit")
+            .and_then(|limit| parser::parse_memory_limit_mb(&limit))
+    }
+    fn float_tolerance(&self) -> Option<f64> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("div.problembody").expect("invalid selector");
+        document.select(&selector).next().and_then(|statement| {
+            parser::parse_float_tolerance(&statement.text().collect::<String>())
+        })
+    }
+}
+
+pub struct KattisSite;
+
+impl Site for KattisSite {
+    fn name(&self) -> &'static str {
+        "Kattis"
+    }
+    // e.g open.kattis.com, and subdomains of universities such as kth.kattis.com
+    fn matches(&self, host: &str) -> bool {
+        host == "kattis.com" || host.ends_with(".kattis.com")
+    }
+    fn parser(&self, document: &str) -> Result<Box<dyn Parser>, anyhow::Error> {
+        Ok(Box::new(KattisParser::new(document)))
+    }
+    // `/problems/hello` -> ("problems", "hello"), `/contests/abc/problems/hello` -> ("abc", "hello")
+    fn contest_and_index(&self, url: &url::Url) -> Option<(String, String)> {
+        match url.path_segments()?.collect::<Vec<_>>().as_slice() {
+            ["problems", problem] => Some(("problems".to_string(), problem.to_string())),
+            ["contests", contest, "problems", problem] => {
+                Some((contest.to_string(), problem.to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KattisParser;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_fixture() {
+        let parser = KattisParser::new(include_str!("../tests/fixtures/kattis/different.html"));
+        assert_eq!(
+            parser.problem_name(),
+            Some("A Different Problem".to_string())
+        );
+        assert_eq!(parser.contest_name(), Some("Kattis".to_string()));
+        assert_eq!(parser.time_limit_ms(), Some(1000));
+        assert_eq!(parser.memory_limit_mb(), Some(1024));
+        assert_eq!(parser.float_tolerance(), None);
+        assert_eq!(
+            parser.sample_cases(),
+            vec![(
+                "10 12\n71293781758123 72784\n1 12345677654321\n".to_string(),
+                "2\n71293781685339\n12345677654320\n".to_string()
+            )]
+        );
+    }
+}
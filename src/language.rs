@@ -0,0 +1,232 @@
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// How to build and run a source file of a language.
+///
+/// Commands can contain these placeholders:
+/// - `{source}`: the source file. e.g `./main.cpp`
+/// - `{binary}`: the file the compiler creates. e.g `./main`
+/// - `{dir}`: the directory of the source file. e.g `.`
+/// - `{stem}`: the file name without its extension. e.g `main`
+/// - `{flags}`: `flags` split into words
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LanguageProfile {
+    pub name: String,
+    /// A file extension without a dot. e.g `cpp`
+    pub extension: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compile: Option<String>,
+    pub run: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub flags: String,
+    /// Where the compiler writes its output. It's compared with the source to skip a build.
+    #[serde(default = "default_binary")]
+    pub binary: String,
+    /// The language name on submit pages or a word of it. e.g `C++` matches `C++ 20 (gcc 12.2)`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submit_language: Option<String>,
+    /// `submit_language` for a host when its names differ. e.g `{"codeforces.com": "G++17"}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub submit_languages: BTreeMap<String, String>,
+    /// Whether `cpm test` caps the address space of the program with some headroom over the memory limit.
+    /// Turn it off for runtimes which reserve much more than they use, such as the JVM.
+    /// The peak RSS is compared with the memory limit either way.
+    #[serde(default = "default_cap_address_space")]
+    pub cap_address_space: bool,
+}
+
+fn default_binary() -> String {
+    "{dir}/{stem}".to_string()
+}
+
+fn default_cap_address_space() -> bool {
+    true
+}
+
+pub fn default_profiles() -> Vec<LanguageProfile> {
+    vec![
+        LanguageProfile {
+            name: "rust".to_string(),
+            extension: "rs".to_string(),
+            compile: Some("rustc {flags} -o {binary} {source}".to_string()),
+            run: "{binary}".to_string(),
+            flags: "-O --edition 2021".to_string(),
+            binary: default_binary(),
+            submit_language: Some("Rust".to_string()),
+            submit_languages: BTreeMap::new(),
+            cap_address_space: true,
+        },
+        LanguageProfile {
+            name: "cpp".to_string(),
+            extension: "cpp".to_string(),
+            compile: Some("g++ {flags} -o {binary} {source}".to_string()),
+            run: "{binary}".to_string(),
+            flags: "-std=gnu++17 -O2 -Wall".to_string(),
+            binary: default_binary(),
+            submit_language: Some("C++".to_string()),
+            submit_languages: BTreeMap::from([("codeforces.com".to_string(), "G++".to_string())]),
+            cap_address_space: true,
+        },
+        LanguageProfile {
+            name: "python".to_string(),
+            extension: "py".to_string(),
+            compile: None,
+            run: "python3 {source}".to_string(),
+            flags: "".to_string(),
+            binary: default_binary(),
+            submit_language: Some("Python".to_string()),
+            submit_languages: BTreeMap::from([(
+                "codeforces.com".to_string(),
+                "Python 3".to_string(),
+            )]),
+            cap_address_space: true,
+        },
+        LanguageProfile {
+            name: "java".to_string(),
+            extension: "java".to_string(),
+            compile: Some("javac {flags} -d {dir} {source}".to_string()),
+            run: "java -cp {dir} {stem}".to_string(),
+            flags: "-encoding UTF-8".to_string(),
+            binary: "{dir}/{stem}.class".to_string(),
+            submit_language: Some("Java".to_string()),
+            submit_languages: BTreeMap::new(),
+            // The JVM reserves its heap up front and fails to start under a tight cap
+            cap_address_space: false,
+        },
+    ]
+}
+
+/// Finds a profile by the extension of `source`.
+pub fn find_profile<'a>(
+    profiles: &'a [LanguageProfile],
+    source: &Path,
+) -> Option<&'a LanguageProfile> {
+    let extension = source.extension()?.to_str()?;
+    profiles
+        .iter()
+        .find(|profile| profile.extension == extension)
+}
+
+impl LanguageProfile {
+    pub fn compile_command(&self, source: &Path) -> Result<Option<Vec<String>>, anyhow::Error> {
+        self.compile
+            .as_ref()
+            .map(|compile| self.expand(compile, source))
+            .transpose()
+    }
+    pub fn run_command(&self, source: &Path) -> Result<Vec<String>, anyhow::Error> {
+        self.expand(&self.run, source)
+    }
+    pub fn binary_path(&self, source: &Path) -> Result<PathBuf, anyhow::Error> {
+        Ok(PathBuf::from(self.expand(&self.binary, source)?.join(" ")))
+    }
+    /// The name to find the language on a submit page of `host`
+    pub fn submit_language_for(&self, host: &str) -> Option<&str> {
+        self.submit_languages
+            .get(host)
+            .or(self.submit_language.as_ref())
+            .map(|name| name.as_str())
+    }
+    /// Whether `source` has to be compiled, i.e. it has never been compiled or it's newer than the binary.
+    pub fn needs_compile(&self, source: &Path) -> Result<bool, anyhow::Error> {
+        if self.compile.is_none() {
+            return Ok(false);
+        }
+        let binary = self.binary_path(source)?;
+        let source_modified = std::fs::metadata(source)?.modified()?;
+        Ok(
+            match std::fs::metadata(binary).and_then(|binary| binary.modified()) {
+                Ok(binary_modified) => source_modified > binary_modified,
+                Err(_) => true,
+            },
+        )
+    }
+
+    // Splits `template` into words and substitutes the placeholders of each word.
+    // A path with spaces stays in one word.
+    fn expand(&self, template: &str, source: &Path) -> Result<Vec<String>, anyhow::Error> {
+        let dir = match source.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let stem = source
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| anyhow::anyhow!("Invalid source file: {}", source.display()))?;
+        // `main.cpp` can't be run as `main` without a directory
+        let source = dir.join(source.file_name().unwrap());
+        let binary = self
+            .binary
+            .replace("{dir}", &dir.to_string_lossy())
+            .replace("{stem}", stem);
+
+        let mut words = vec![];
+        for word in util::split_command(template)? {
+            if word == "{flags}" {
+                words.extend(util::split_command(&self.flags)?);
+                continue;
+            }
+            words.push(
+                word.replace("{source}", &source.to_string_lossy())
+                    .replace("{binary}", &binary)
+                    .replace("{dir}", &dir.to_string_lossy())
+                    .replace("{stem}", stem),
+            );
+        }
+        Ok(words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_profiles, find_profile};
+    use std::path::Path;
+
+    #[test]
+    fn test_commands() {
+        let profiles = default_profiles();
+        let cpp = find_profile(&profiles, Path::new("main.cpp")).unwrap();
+        assert_eq!(
+            cpp.compile_command(Path::new("main.cpp")).unwrap(),
+            Some(
+                vec![
+                    "g++",
+                    "-std=gnu++17",
+                    "-O2",
+                    "-Wall",
+                    "-o",
+                    "./main",
+                    "./main.cpp"
+                ]
+                .into_iter()
+                .map(|s| s.to_string())
+                .collect()
+            )
+        );
+        assert_eq!(
+            cpp.run_command(Path::new("my dir/main.cpp")).unwrap(),
+            vec!["my dir/main".to_string()]
+        );
+
+        let python = find_profile(&profiles, Path::new("a/b.py")).unwrap();
+        assert_eq!(python.compile_command(Path::new("a/b.py")).unwrap(), None);
+        assert_eq!(
+            python.run_command(Path::new("a/b.py")).unwrap(),
+            vec!["python3".to_string(), "a/b.py".to_string()]
+        );
+
+        let java = find_profile(&profiles, Path::new("Main.java")).unwrap();
+        assert_eq!(
+            java.binary_path(Path::new("Main.java")).unwrap(),
+            Path::new("./Main.class")
+        );
+        assert!(!java.cap_address_space);
+        assert!(cpp.cap_address_space);
+        assert!(find_profile(&profiles, Path::new("main.hs")).is_none());
+
+        assert_eq!(cpp.submit_language_for("atcoder.jp"), Some("C++"));
+        assert_eq!(cpp.submit_language_for("codeforces.com"), Some("G++"));
+    }
+}
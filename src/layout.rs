@@ -0,0 +1,273 @@
+//! Where `cpm get` creates a problem directory under the root path.
+//!
+//! A layout is a `/` separated path template with these placeholders:
+//! - `{host}`: e.g `atcoder.jp`
+//! - `{path}`: the components of the URL path. e.g `contests/abc154/tasks/abc154_a`
+//! - `{contest_id}`: e.g `abc154`, `4`
+//! - `{problem_index}`: e.g `a` on AtCoder, `A` on Codeforces
+//! - `{problem_id}`: the last component of the URL path. e.g `abc154_a`
+//! - `{contest_name}`, `{problem_name}`
+
+use crate::index::WorkspaceIndex;
+use crate::site;
+use crate::util::ProblemInfo;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// `root/atcoder.jp/contests/abc154/tasks/abc154_a`
+pub fn default_layout() -> String {
+    "{host}/{path}".to_string()
+}
+
+/// Replaces characters which can't be in a file name on Windows or Unix with `_`.
+pub fn sanitize(name: &str) -> String {
+    let name = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>();
+    // A trailing dot or space is dropped on Windows. `.` and `..` aren't names
+    let name = name.trim().trim_end_matches('.').trim();
+    if name.is_empty() {
+        "_".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// The directory of `info`'s problem under `root`
+pub fn problem_dir(
+    root: &Path,
+    layout: &str,
+    info: &ProblemInfo,
+) -> Result<PathBuf, anyhow::Error> {
+    let url = url::Url::parse(&info.url)?;
+    let host = url
+        .host_str()
+        .ok_or_else(|| anyhow::anyhow!("Invalid problem URL: {}", info.url))?;
+    let paths = url
+        .path_segments()
+        .map(|paths| {
+            paths
+                .filter(|path| !path.is_empty())
+                .map(|path| path.to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let problem_id = paths.last().cloned().unwrap_or_default();
+    // Saved by `cpm get`, or given by the site for an old `.problem.json`
+    let (site_contest_id, site_problem_index) = site::find(&url)
+        .and_then(|site| site.contest_and_index(&url))
+        .unzip();
+    let contest_id = info.contest_id.clone().or(site_contest_id);
+    let problem_index = info.problem_index.clone().or(site_problem_index);
+
+    let mut dir = root.to_path_buf();
+    for component in layout.split('/').filter(|component| !component.is_empty()) {
+        if component == "{path}" {
+            paths.iter().for_each(|path| dir.push(sanitize(path)));
+            continue;
+        }
+        let mut name = component.to_string();
+        let values = [
+            ("{host}", Some(host.to_string())),
+            ("{contest_id}", contest_id.clone()),
+            ("{problem_index}", problem_index.clone()),
+            ("{problem_id}", Some(problem_id.clone())),
+            ("{contest_name}", Some(info.contest_name.clone())),
+            ("{problem_name}", Some(info.problem_name.clone())),
+        ];
+        for (placeholder, value) in values.iter() {
+            if !name.contains(placeholder) {
+                continue;
+            }
+            let value = value
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("{} of {} is unknown", placeholder, info.url))?;
+            name = name.replace(placeholder, &sanitize(value));
+        }
+        dir.push(sanitize(&name));
+    }
+    if dir == root {
+        return Err(anyhow::anyhow!("The layout is empty: {}", layout));
+    }
+    Ok(dir)
+}
+
+/// What `cpm migrate-layout` does
+#[derive(Debug, Default, PartialEq)]
+pub struct MigrationPlan {
+    /// Pairs of a current directory and its directory in the layout, for problems that have to be moved
+    pub moves: Vec<(PathBuf, PathBuf)>,
+    /// Directories whose new path can't be built, with the reason. e.g the site of the URL is unknown
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+/// Plans moving every problem in `index` to its directory in `layout`.
+/// A problem is left where it is if another problem or file is already at the new directory.
+pub fn plan_migration(index: &WorkspaceIndex, layout: &str) -> MigrationPlan {
+    // The directories in `index` are canonicalized
+    let root = Path::new(&index.root);
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let mut taken = index
+        .problems
+        .keys()
+        .map(PathBuf::from)
+        .collect::<BTreeSet<_>>();
+    let mut plan = MigrationPlan::default();
+    for (dir, info) in index.problems.iter() {
+        let from = PathBuf::from(dir);
+        let to = match problem_dir(&root, layout, info) {
+            Ok(to) => to,
+            Err(e) => {
+                plan.skipped.push((from, e.to_string()));
+                continue;
+            }
+        };
+        if to == from || taken.contains(&to) || to.exists() {
+            continue;
+        }
+        taken.insert(to.clone());
+        plan.moves.push((from, to));
+    }
+    plan
+}
+
+/// Removes `dir` and its parents while they're empty, up to `root`
+pub fn remove_empty_parents(dir: &Path, root: &Path) {
+    let mut dir = Some(dir);
+    while let Some(current) = dir {
+        if current == root || !current.starts_with(root) || std::fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{default_layout, plan_migration, problem_dir, remove_empty_parents, sanitize};
+    use crate::index::WorkspaceIndex;
+    use crate::util::{problem_info, TempDir};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_sanitize() {
+        assert_eq!(sanitize("A - Remaining Balls"), "A - Remaining Balls");
+        assert_eq!(sanitize("A/B: C?"), "A_B_ C_");
+        assert_eq!(sanitize(" Codeforces Round 1 \n"), "Codeforces Round 1");
+        assert_eq!(sanitize("a\tb"), "a_b");
+        assert_eq!(sanitize(".."), "_");
+        assert_eq!(sanitize(""), "_");
+    }
+
+    #[test]
+    fn test_problem_dir() {
+        let root = Path::new("/root");
+        let abc = problem_info(
+            "https://atcoder.jp/contests/abc154/tasks/abc154_a",
+            "AtCoder Beginner Contest 154",
+            "A - Remaining Balls",
+            None,
+        );
+        let mut cf = problem_info(
+            "https://codeforces.com/contest/4/problem/A",
+            "Codeforces Beta Round #4 (Div. 2 Only)",
+            "A. Watermelon",
+            None,
+        );
+        cf.contest_id = Some("4".to_string());
+        cf.problem_index = Some("A".to_string());
+
+        assert_eq!(
+            problem_dir(root, &default_layout(), &abc).unwrap(),
+            PathBuf::from("/root/atcoder.jp/contests/abc154/tasks/abc154_a")
+        );
+        let layout = "{host}/{contest_id}/{problem_index}";
+        assert_eq!(
+            problem_dir(root, layout, &abc).unwrap(),
+            PathBuf::from("/root/atcoder.jp/abc154/a")
+        );
+        assert_eq!(
+            problem_dir(root, layout, &cf).unwrap(),
+            PathBuf::from("/root/codeforces.com/4/A")
+        );
+        assert_eq!(
+            problem_dir(root, "{contest_name}/{problem_id} {problem_name}", &abc).unwrap(),
+            PathBuf::from("/root/AtCoder Beginner Contest 154/abc154_a A - Remaining Balls")
+        );
+        let unknown = problem_info("https://example.com/", "", "", None);
+        assert!(problem_dir(root, layout, &unknown).is_err());
+        assert!(problem_dir(root, "/", &abc).is_err());
+    }
+
+    #[test]
+    fn test_migration() {
+        let temp_dir = TempDir::new("layout");
+        let root = temp_dir.path();
+        let mut index = WorkspaceIndex {
+            root: root.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let problems = [
+            (
+                "atcoder.jp/contests/abc154/tasks/abc154_a",
+                "https://atcoder.jp/contests/abc154/tasks/abc154_a",
+            ),
+            (
+                "yukicoder.me/problems/no/1",
+                "https://yukicoder.me/problems/no/1",
+            ),
+            (
+                "onlinejudge.u-aizu.ac.jp/problems/ALDS1_1_B",
+                "https://onlinejudge.u-aizu.ac.jp/problems/ALDS1_1_B",
+            ),
+            (
+                "cses.fi/problemset/task/1068",
+                "https://cses.fi/problemset/task/1068",
+            ),
+            (
+                "open.kattis.com/problems/hello",
+                "https://open.kattis.com/problems/hello",
+            ),
+            ("example.com/a", "https://example.com/a"),
+        ];
+        for (dir, url) in problems.iter() {
+            let dir = root.join(dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            index.upsert(&dir, problem_info(url, "", "", None));
+        }
+        let plan = plan_migration(&index, &default_layout());
+        assert!(plan.moves.is_empty());
+        assert!(plan.skipped.is_empty());
+
+        // A problem of an unknown site is skipped and the others are still moved.
+        // Kattis problems are already at `problems/hello`
+        let plan = plan_migration(&index, "{host}/{contest_id}/{problem_index}");
+        assert_eq!(
+            plan.moves
+                .iter()
+                .map(|(_, to)| to.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                root.join("atcoder.jp/abc154/a"),
+                root.join("cses.fi/problemset/1068"),
+                root.join("onlinejudge.u-aizu.ac.jp/ALDS1_1/B"),
+                root.join("yukicoder.me/no/1"),
+            ]
+        );
+        assert_eq!(plan.skipped.len(), 1);
+        assert_eq!(plan.skipped[0].0, root.join("example.com/a"));
+
+        let (old, new) = &plan.moves[0];
+        std::fs::create_dir_all(new.parent().unwrap()).unwrap();
+        std::fs::rename(old, new).unwrap();
+        remove_empty_parents(old.parent().unwrap(), root);
+        assert!(!root.join("atcoder.jp/contests").exists());
+        assert!(root.join("atcoder.jp/abc154/a").exists());
+    }
+}
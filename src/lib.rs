@@ -1,5 +1,22 @@
+pub mod aoj;
 pub mod atcoder;
+pub mod checker;
 pub mod codeforces;
+pub mod cses;
 pub mod executor;
+pub mod index;
+pub mod kattis;
+pub mod language;
+pub mod layout;
+pub mod listing;
+pub mod metadata;
+#[cfg(test)]
+mod mock_server;
 pub mod parser;
+pub mod samples;
+pub mod session;
+pub mod site;
+pub mod submit;
+pub mod template;
 pub mod util;
+pub mod yukicoder;
@@ -0,0 +1,264 @@
+//! Output formats of `cpm list`.
+
+use crate::util::ProblemInfo;
+use serde::Serialize;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ListFormat {
+    /// Directories only, or `contest problem directory` with `--all`
+    Plain,
+    /// Aligned columns with a header
+    Table,
+    Json,
+    Csv,
+}
+
+impl std::str::FromStr for ListFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(ListFormat::Plain),
+            "table" => Ok(ListFormat::Table),
+            "json" => Ok(ListFormat::Json),
+            "csv" => Ok(ListFormat::Csv),
+            _ => Err(anyhow::anyhow!("Unknown format: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ListSort {
+    Path,
+    /// Oldest first. Problems without `created_at` come last
+    CreatedAt,
+}
+
+impl std::str::FromStr for ListSort {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "path" => Ok(ListSort::Path),
+            "created_at" => Ok(ListSort::CreatedAt),
+            _ => Err(anyhow::anyhow!("Unknown sort key: {}", s)),
+        }
+    }
+}
+
+/// A row of `--format json` and `--format csv`
+#[derive(Serialize)]
+struct ListRow<'a> {
+    path: &'a str,
+    url: &'a str,
+    contest_name: String,
+    problem_name: String,
+    /// RFC 3339 in UTC
+    created_at: Option<String>,
+    status: String,
+    difficulty: Option<i64>,
+    point: Option<f64>,
+}
+
+impl<'a> ListRow<'a> {
+    fn new(dir: &'a str, info: &'a ProblemInfo) -> ListRow<'a> {
+        ListRow {
+            path: dir,
+            url: &info.url,
+            contest_name: clean_name(&info.contest_name),
+            problem_name: clean_name(&info.problem_name),
+            created_at: info.created_at.map(|created_at| created_at.to_rfc3339()),
+            status: info.solve_status().to_string(),
+            difficulty: info.difficulty,
+            point: info.point,
+        }
+    }
+}
+
+// Names scraped from a page may contain newlines and tabs
+fn clean_name(name: &str) -> String {
+    name.chars()
+        .filter(|&c| c != '\n' && c != '\t')
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+// Quotes a field if it has a comma, a quote or a newline (RFC 4180)
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub fn sort(entries: &mut [(&str, &ProblemInfo)], sort: ListSort) {
+    match sort {
+        ListSort::Path => entries.sort_by(|a, b| a.0.cmp(b.0)),
+        ListSort::CreatedAt => entries.sort_by_key(|(dir, info)| {
+            (info.created_at.is_none(), info.created_at, dir.to_string())
+        }),
+    }
+}
+
+/// Renders problems with their directories. `all` prints names in the plain format.
+/// Dates of the table are in the local time zone.
+pub fn render(entries: &[(&str, &ProblemInfo)], format: ListFormat, all: bool) -> String {
+    match format {
+        ListFormat::Plain => entries
+            .iter()
+            .map(|(dir, info)| {
+                if all {
+                    format!(
+                        "{} {} {}\n",
+                        clean_name(&info.contest_name),
+                        clean_name(&info.problem_name),
+                        dir
+                    )
+                } else {
+                    format!("{}\n", dir)
+                }
+            })
+            .collect(),
+        ListFormat::Table => {
+            let header = ["CONTEST", "PROBLEM", "STATUS", "CREATED", "PATH"];
+            let rows = entries
+                .iter()
+                .map(|(dir, info)| {
+                    vec![
+                        clean_name(&info.contest_name),
+                        clean_name(&info.problem_name),
+                        info.solve_status().to_string(),
+                        info.created_at
+                            .map(|created_at| {
+                                created_at
+                                    .with_timezone(&chrono::Local)
+                                    .format("%Y-%m-%d %H:%M")
+                                    .to_string()
+                            })
+                            .unwrap_or_else(|| "-".to_string()),
+                        dir.to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            let widths = (0..header.len())
+                .map(|col| {
+                    rows.iter()
+                        .map(|row| row[col].chars().count())
+                        .chain(std::iter::once(header[col].len()))
+                        .max()
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            let header = header.iter().map(|name| name.to_string()).collect();
+            std::iter::once(header)
+                .chain(rows)
+                .map(|row: Vec<String>| {
+                    let line = row
+                        .iter()
+                        .zip(widths.iter())
+                        .map(|(cell, width)| {
+                            let padding = width - cell.chars().count();
+                            format!("{}{}", cell, " ".repeat(padding))
+                        })
+                        .collect::<Vec<_>>()
+                        .join("  ");
+                    format!("{}\n", line.trim_end())
+                })
+                .collect()
+        }
+        ListFormat::Json => {
+            let rows = entries
+                .iter()
+                .map(|(dir, info)| ListRow::new(dir, info))
+                .collect::<Vec<_>>();
+            format!("{}\n", serde_json::to_string_pretty(&rows).unwrap())
+        }
+        ListFormat::Csv => {
+            let mut csv = "path,url,contest_name,problem_name,created_at,status,difficulty,point\n"
+                .to_string();
+            for (dir, info) in entries.iter() {
+                let row = ListRow::new(dir, info);
+                let fields = [
+                    row.path.to_string(),
+                    row.url.to_string(),
+                    row.contest_name,
+                    row.problem_name,
+                    row.created_at.unwrap_or_default(),
+                    row.status,
+                    row.difficulty.map(|d| d.to_string()).unwrap_or_default(),
+                    row.point.map(|p| p.to_string()).unwrap_or_default(),
+                ];
+                let fields = fields
+                    .iter()
+                    .map(|field| csv_field(field))
+                    .collect::<Vec<_>>();
+                csv.push_str(&fields.join(","));
+                csv.push('\n');
+            }
+            csv
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render, sort, ListFormat, ListSort};
+    use crate::util::problem_info;
+
+    const URL: &str = "https://atcoder.jp/contests/abc154/tasks/abc154_a";
+
+    #[test]
+    fn test_sort() {
+        let (a, b, c) = (
+            problem_info(URL, "ABC154", "A", Some(1581249600)),
+            problem_info(URL, "ABC154", "B", None),
+            problem_info(URL, "ABC154", "C", Some(1500000000)),
+        );
+        let mut entries = vec![("/b", &b), ("/c", &c), ("/a", &a)];
+        sort(&mut entries, ListSort::CreatedAt);
+        assert_eq!(
+            entries.iter().map(|(dir, _)| *dir).collect::<Vec<_>>(),
+            vec!["/c", "/a", "/b"]
+        );
+        sort(&mut entries, ListSort::Path);
+        assert_eq!(
+            entries.iter().map(|(dir, _)| *dir).collect::<Vec<_>>(),
+            vec!["/a", "/b", "/c"]
+        );
+    }
+
+    #[test]
+    fn test_render() {
+        let a = problem_info(URL, "\n\tABC154\n", "A - Remaining Balls", Some(1581249600));
+        let b = problem_info(URL, "ABC154", "B, \"quoted\"", None);
+        let entries = vec![("/a", &a), ("/b", &b)];
+
+        assert_eq!(render(&entries, ListFormat::Plain, false), "/a\n/b\n");
+        assert_eq!(
+            render(&entries, ListFormat::Plain, true),
+            "ABC154 A - Remaining Balls /a\nABC154 B, \"quoted\" /b\n"
+        );
+
+        let table = render(&entries[1..], ListFormat::Table, false);
+        assert_eq!(
+            table,
+            "CONTEST  PROBLEM      STATUS    CREATED  PATH\n\
+             ABC154   B, \"quoted\"  unsolved  -        /b\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&entries, ListFormat::Json, false)).unwrap();
+        assert_eq!(json[0]["contest_name"], "ABC154");
+        assert_eq!(json[0]["created_at"], "2020-02-09T12:00:00+00:00");
+        assert_eq!(json[0]["status"], "unsolved");
+        assert_eq!(json[1]["created_at"], serde_json::Value::Null);
+
+        let csv = render(&entries, ListFormat::Csv, false);
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[2],
+            "/b,https://atcoder.jp/contests/abc154/tasks/abc154_a,ABC154,\"B, \"\"quoted\"\"\",,unsolved,,"
+        );
+    }
+}
@@ -1,15 +1,21 @@
-use chrono::Utc;
+use chrono::{TimeZone, Utc};
 use colored::*;
-use cpm::codeforces::CodeforcesParser;
+use cpm::checker::CheckerConfig;
+use cpm::executor::{self, Executor};
+use cpm::index::{self, IndexQuery, WorkspaceIndex};
+use cpm::language::{self, LanguageProfile};
+use cpm::layout;
+use cpm::listing::{self, ListFormat, ListSort};
+use cpm::metadata::{self, MetadataIndex};
 use cpm::parser::Parser;
-use cpm::util;
-use cpm::{atcoder::AtCoderParser, util::ProblemInfo};
-use reqwest::header::{HeaderMap, HeaderValue, COOKIE};
+use cpm::samples;
+use cpm::session::{self, CookieJar};
+use cpm::site::{self, Site};
+use cpm::submit::{Language, SubmissionResult, SubmitError, Verdict};
+use cpm::template::{self, TemplateConfig};
+use cpm::util::{self, ProblemInfo, SolveStatus, TestRecord};
 use serde::{Deserialize, Serialize};
-use std::{
-    fs::File,
-    io::{BufReader, Read},
-};
+use std::io::Read;
 
 enum SubCommand {
     Init,
@@ -20,6 +26,11 @@ enum SubCommand {
     Root,
     List,
     Test,
+    Submit,
+    Status,
+    SyncMetadata,
+    Reindex,
+    MigrateLayout,
 }
 impl SubCommand {
     fn value(&self) -> String {
@@ -32,13 +43,36 @@ impl SubCommand {
             SubCommand::Root => "root".to_string(),
             SubCommand::List => "list".to_string(),
             SubCommand::Test => "test".to_string(),
+            SubCommand::Submit => "submit".to_string(),
+            SubCommand::Status => "status".to_string(),
+            SubCommand::SyncMetadata => "sync-metadata".to_string(),
+            SubCommand::Reindex => "reindex".to_string(),
+            SubCommand::MigrateLayout => "migrate-layout".to_string(),
         }
     }
 }
 
+// Used by `cpm test` when neither the problem nor the command line specifies a time limit
+const DEFAULT_TIME_LIMIT: std::time::Duration = std::time::Duration::from_secs(2);
+// Used by `cpm test` when neither the problem nor the command line specifies a memory limit
+const DEFAULT_MEMORY_LIMIT_MB: u64 = 1024;
+const MEMORY_CAP_FACTOR: u64 = 2;
+// Used by `cpm test --checker float` when no tolerance is known
+const DEFAULT_FLOAT_TOLERANCE: f64 = 1e-6;
+// How often `cpm status` reloads the submissions while judging
+const STATUS_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
 #[derive(Serialize, Deserialize)]
 struct Config {
     root: String,
+    #[serde(default = "language::default_profiles")]
+    languages: Vec<LanguageProfile>,
+    /// A path template of problem directories under `root`. e.g `{host}/{contest_id}/{problem_index}`
+    #[serde(default = "layout::default_layout")]
+    layout: String,
+    /// Source files copied into a new problem directory
+    #[serde(default)]
+    templates: TemplateConfig,
 }
 
 fn init_config() -> Result<(), anyhow::Error> {
@@ -52,6 +86,9 @@ fn init_config() -> Result<(), anyhow::Error> {
     if !config_file.exists() {
         let config = Config {
             root: "".to_string(),
+            languages: language::default_profiles(),
+            layout: layout::default_layout(),
+            templates: TemplateConfig::default(),
         };
         serde_json::to_writer(&std::fs::File::create(config_file.clone())?, &config)?;
     }
@@ -86,58 +123,277 @@ fn load_config() -> Result<Config, anyhow::Error> {
     let config: Config = serde_json::from_reader(reader)?;
     Ok(config)
 }
+// Loads the index of the root path. It's built on the first use or when the root path is changed.
+fn load_index(root: &str) -> Result<WorkspaceIndex, anyhow::Error> {
+    let index_path = index::index_path();
+    match WorkspaceIndex::load(&index_path)? {
+        Some(index) if index.root == root => Ok(index),
+        _ => {
+            let index = WorkspaceIndex::rebuild(std::path::Path::new(root));
+            index.save(&index_path)?;
+            Ok(index)
+        }
+    }
+}
+// Writes `.problem.json` and updates the index if `dir` is under the root path.
+// The index is only a cache of `cpm list`, so failing to update it doesn't fail the command.
+fn save_problem_info(info: ProblemInfo, dir: &std::path::Path) -> Result<(), anyhow::Error> {
+    util::create_problem_info_json(info.clone(), dir)?;
+    if let Err(err) = update_index(info, dir) {
+        println!("Failed to update the index: {}", err);
+    }
+    Ok(())
+}
+// Skipped without a config or a root path
+fn update_index(info: ProblemInfo, dir: &std::path::Path) -> Result<(), anyhow::Error> {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(_) => return Ok(()),
+    };
+    if config.root.trim().is_empty() {
+        return Ok(());
+    }
+    let dir = dir.canonicalize()?;
+    let root = std::path::Path::new(&config.root);
+    if dir.starts_with(root) || root.canonicalize().is_ok_and(|root| dir.starts_with(root)) {
+        let mut index = load_index(&config.root)?;
+        index.upsert(&dir, info);
+        index.save(&index::index_path())?;
+    }
+    Ok(())
+}
+fn print_stdout(stdout: &str) {
+    if !stdout.trim().is_empty() {
+        println!("The stdout is");
+        println!("{}", stdout.trim_end());
+    }
+}
+fn print_stderr(stderr: &str) {
+    if !stderr.trim().is_empty() {
+        println!("The stderr is");
+        println!("{}", stderr.trim_end());
+    }
+}
+
+// e.g `-m 256` -> 268435456
+fn memory_limit_bytes(memory_limit_mb: u64) -> Result<u64, anyhow::Error> {
+    memory_limit_mb
+        .checked_mul(1024 * 1024)
+        .ok_or_else(|| anyhow::anyhow!("Too large memory limit: {} MB", memory_limit_mb))
+}
+
+fn print_summary(ac_cnt: usize, total: usize) {
+    let status = if ac_cnt == total {
+        format!("{} : {} / {}", "[Accept]".green(), ac_cnt, total)
+    } else {
+        format!("{} : {} / {}", "[Wrong Answer]".yellow(), ac_cnt, total)
+    };
+    println!("{}", status);
+}
+
+// Saves the result of `cpm test` if the current directory is a problem
+fn record_test(passed: usize, total: usize) -> Result<(), anyhow::Error> {
+    let dir = std::path::Path::new(".");
+    let mut info = match util::load_problem_info(dir) {
+        Ok(info) => info,
+        Err(_) => return Ok(()),
+    };
+    info.last_test = Some(TestRecord {
+        ran_at: Utc::now(),
+        passed,
+        failed: total - passed,
+    });
+    save_problem_info(info, dir)
+}
+fn print_submission_result(result: &SubmissionResult) {
+    let verdict = format!("[{}]", result.verdict);
+    let verdict = match result.verdict {
+        Verdict::Accepted => verdict.green(),
+        Verdict::RuntimeError => verdict.red(),
+        Verdict::Judging => verdict.cyan(),
+        _ => verdict.yellow(),
+    };
+    let mut line = format!("{} {} ({})", verdict, result.problem_name, result.language);
+    match (result.test_case, result.total_test_cases) {
+        (Some(test_case), Some(total)) => line.push_str(&format!(" {}/{}", test_case, total)),
+        (Some(test_case), None) => line.push_str(&format!(" on test {}", test_case)),
+        _ => {}
+    }
+    if let Some(exec_time_ms) = result.exec_time_ms {
+        line.push_str(&format!(" {} ms", exec_time_ms));
+    }
+    if let Some(memory_kb) = result.memory_kb {
+        line.push_str(&format!(" {} KB", memory_kb));
+    }
+    println!("{}", line);
+}
+
+// A program `cpm test` runs on each case
+struct TestCommand {
+    command: Vec<String>,
+    /// `LanguageProfile::cap_address_space` of the source's profile
+    cap_address_space: bool,
+}
+
+impl TestCommand {
+    // The executor of the command under `memory_limit` bytes
+    fn executor(&self, memory_limit: u64) -> Result<Executor, anyhow::Error> {
+        let executor = command_executor(&self.command)?;
+        if !self.cap_address_space {
+            return Ok(executor);
+        }
+        // The address space is capped with some headroom so that a runaway solution can't
+        // exhaust the machine. The verdict compares the peak RSS with the limit itself.
+        Ok(executor.memory_limit(memory_limit.saturating_mul(MEMORY_CAP_FACTOR)))
+    }
+}
+
+fn command_executor(command: &[String]) -> Result<Executor, anyhow::Error> {
+    let program = command
+        .first()
+        .ok_or_else(|| anyhow::anyhow!("No command"))?;
+    Ok(Executor::new(program).args(command.iter().skip(1)))
+}
+
+#[derive(Default)]
+struct TestOptions {
+    time_limit: Option<std::time::Duration>,
+    memory_limit_mb: Option<u64>,
+    float_tolerance: Option<f64>,
+    checker: Option<String>,
+    special_judge: Option<String>,
+    interactor: Option<String>,
+}
+
+struct ListOptions {
+    all: bool,
+    recent: bool,
+    since: Option<chrono::DateTime<Utc>>,
+    until: Option<chrono::DateTime<Utc>>,
+    host: Option<String>,
+    contest: Option<String>,
+    status: Option<SolveStatus>,
+    min_difficulty: Option<i64>,
+    max_difficulty: Option<i64>,
+    min_point: Option<f64>,
+    max_point: Option<f64>,
+    contest_since: Option<chrono::DateTime<Utc>>,
+    contest_until: Option<chrono::DateTime<Utc>>,
+    sort: ListSort,
+    format: ListFormat,
+}
+
+impl ListOptions {
+    fn filters_metadata(&self) -> bool {
+        self.min_difficulty.is_some()
+            || self.max_difficulty.is_some()
+            || self.min_point.is_some()
+            || self.max_point.is_some()
+            || self.contest_since.is_some()
+            || self.contest_until.is_some()
+    }
+    // Problems without the metadata don't match a filter of it
+    fn matches_metadata(&self, info: &ProblemInfo) -> bool {
+        fn within<T: PartialOrd + Copy>(value: Option<T>, min: Option<T>, max: Option<T>) -> bool {
+            if min.is_none() && max.is_none() {
+                return true;
+            }
+            value.is_some_and(|value| {
+                min.is_none_or(|min| min <= value) && max.is_none_or(|max| value <= max)
+            })
+        }
+        within(info.difficulty, self.min_difficulty, self.max_difficulty)
+            && within(info.point, self.min_point, self.max_point)
+            && within(info.contest_start, self.contest_since, self.contest_until)
+    }
+}
+
 struct Cpm {
     client: reqwest::Client,
-    //for request
-    cookie_headers: HeaderMap,
+    //cookies sent by the client. They are saved after login
+    cookie_jar: std::sync::Arc<CookieJar>,
     //from response
     html: Option<String>,
 }
 
 impl Cpm {
     fn new() -> Self {
+        let cookie_jar = std::sync::Arc::new(util::load_cookie_jar().unwrap_or_else(|e| {
+            println!("Failed to load cookies: {:?}", e);
+            CookieJar::default()
+        }));
         let client = reqwest::Client::builder()
-            .cookie_store(true)
+            .cookie_provider(cookie_jar.clone())
             .build()
             .unwrap();
         Cpm {
             client,
-            cookie_headers: HeaderMap::new(),
+            cookie_jar,
             html: None,
         }
     }
 
-    fn create_problem_dir<T: Parser>(
+    fn create_problem_dir<T: Parser + ?Sized>(
         &self,
         url: &url::Url,
         parser: &T,
         sample_verbose: bool,
+        refresh: bool,
     ) -> Result<(), anyhow::Error> {
-        let host_name = url.host_str().unwrap();
         let config = load_config()?;
-        let path = {
-            let mut path = std::path::PathBuf::from(config.root).join(host_name);
-            std::path::PathBuf::from(url.path())
-                .iter()
-                .filter_map(|comp| {
-                    let comp = comp.to_str().unwrap();
-                    if comp != std::path::MAIN_SEPARATOR.to_string() {
-                        Some(comp)
-                    } else {
-                        None
-                    }
-                })
-                .for_each(|name| {
-                    path.push(name);
-                });
-            path
+        let (contest_id, problem_index) = site::find(url)
+            .and_then(|site| site.contest_and_index(url))
+            .unzip();
+        let fetched = ProblemInfo {
+            url: url.to_string(),
+            contest_name: parser.contest_name().expect("failed to get contest name"),
+            problem_name: parser.problem_name().expect("failed to get problem name"),
+            created_at: Some(Utc::now()),
+            time_limit_ms: parser.time_limit_ms(),
+            memory_limit_mb: parser.memory_limit_mb(),
+            float_tolerance: parser.float_tolerance(),
+            checker: None,
+            contest_id,
+            problem_index,
+            difficulty: None,
+            point: None,
+            contest_start: None,
+            last_test: None,
+            submissions: vec![],
+        };
+        let path =
+            layout::problem_dir(std::path::Path::new(&config.root), &config.layout, &fetched)?;
+        // A problem downloaded again keeps its checker, creation time and history
+        let mut info = match util::load_problem_info(&path) {
+            Ok(mut info) => {
+                info.refresh(fetched);
+                info
+            }
+            Err(_) => fetched,
         };
         let samples = parser.sample_cases();
+        let sample_dir = path.join("sample");
+        if refresh && sample_dir.exists() {
+            let old_samples = samples::load_sample_cases(&sample_dir)?;
+            let diff = samples::diff_sample_cases(&old_samples, &samples);
+            if diff.is_empty() {
+                println!("Sample cases are unchanged: {}", path.to_str().unwrap());
+            }
+            for line in diff.iter() {
+                if line.starts_with('-') {
+                    println!("{}", line.red());
+                } else if line.starts_with('+') {
+                    println!("{}", line.green());
+                } else {
+                    println!("{}", line);
+                }
+            }
+        }
 
         if sample_verbose {
             println!("====== Download Result ======");
         }
-        util::create_sample_test_files(&samples, path.join("sample").to_str())?;
+        samples::rewrite_sample_dir(&sample_dir, &samples)?;
         for (idx, (input, output)) in samples.iter().enumerate() {
             if sample_verbose {
                 println!("=== Sample Test Case {} ===", idx + 1);
@@ -148,100 +404,84 @@ impl Cpm {
             println!("=============================");
         }
 
-        let info = ProblemInfo {
-            url: url.to_string(),
-            contest_name: parser.contest_name().expect("failed to get contest name"),
-            problem_name: parser.problem_name().expect("failed to get problem name"),
-            created_at: Some(Utc::now()),
-        };
-        util::create_problem_info_json(info, &path)?;
+        // Synced by `cpm sync-metadata`
+        if let Ok(index) = MetadataIndex::load(&metadata::index_path()) {
+            index.enrich(&mut info);
+        }
+        save_problem_info(info.clone(), &path)?;
         println!(
             "Created a directory and saved sample cases: {}",
             path.to_str().unwrap()
         );
+        for file in template::copy_templates(&config.templates, &info, &path)? {
+            println!("Copied a template: {}", file.to_str().unwrap());
+        }
         Ok(())
     }
     pub fn init(&self) -> Result<(), anyhow::Error> {
         init_config()?;
         Ok(())
     }
-    pub async fn get(&mut self, url: &str) -> Result<(), anyhow::Error> {
-        let url = url::Url::parse(url)?;
-
-        let host = url.host_str();
-        match host {
-            Some("atcoder.jp") => {
-                if let Ok(cookie_headers) = util::local_cookie_headers() {
-                    self.cookie_headers = cookie_headers
-                }
-                let mut paths: Vec<_> = url.path().split('/').collect();
-                if !paths.contains(&"tasks") {
-                    paths.push("tasks");
-                }
-                let path = paths.join("/");
-                let mut url = url;
-                url.set_path(&path);
-
-                let resp = self.call_get_request(url.as_str()).await?;
-                self.parse_response(resp).await?;
-                let parser = AtCoderParser::new(self.html.as_ref().unwrap());
-
-                let query = url.path().split('/').last().expect("No element");
-
-                match query {
-                    "tasks" => {
-                        if let Some(url_list) = parser.problem_url_list() {
-                            for task_url in url_list.iter() {
-                                let task_url = url.join(task_url)?;
-                                let resp = self.call_get_request(task_url.as_str()).await?;
-                                self.parse_response(resp).await?;
-                                let parser = AtCoderParser::new(self.html.as_ref().unwrap());
-                                self.create_problem_dir(&task_url, &parser, false)?;
-                            }
-                        }
-                    }
-                    _ => {
-                        self.create_problem_dir(&url, &parser, true)?;
-                    }
+    // The site of `url`. `get` and `download` support the same sites
+    fn find_site(url: &url::Url) -> Result<Box<dyn Site>, anyhow::Error> {
+        site::find(url).ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} isn't supported yet. X(",
+                url.host_str().unwrap_or_default()
+            )
+        })
+    }
+    async fn fetch_parser(
+        &self,
+        site: &dyn Site,
+        url: &url::Url,
+    ) -> Result<Box<dyn Parser>, anyhow::Error> {
+        let document = site.fetch_document(&self.client, url).await?;
+        site.parser(&document)
+    }
+    // `section` is put in the fragment of `url`, where sites read it
+    pub async fn get(
+        &mut self,
+        url: &str,
+        refresh: bool,
+        section: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        let mut url = url::Url::parse(url)?;
+        if section.is_some() {
+            url.set_fragment(section);
+        }
+        let site = Cpm::find_site(&url)?;
+        let url = site.normalize_url(&url);
+        let document = site.fetch_document(&self.client, &url).await?;
+        match site.problem_url_list(&url, &document) {
+            Some(problem_url_list) => {
+                if problem_url_list.is_empty() {
+                    return Err(anyhow::anyhow!("No problems found in {}", url));
                 }
-            }
-            Some("codeforces.com") => {
-                let resp = self.call_get_request(url.as_str()).await?;
-                self.parse_response(resp).await?;
-                let parser = CodeforcesParser::new(self.html.as_ref().unwrap());
-                let problem_url_list = parser.problem_url_list(url.path());
-
-                if !problem_url_list.is_empty() {
-                    for task_url in problem_url_list.iter() {
-                        let task_url = url.join(task_url)?;
-                        let resp = self.call_get_request(task_url.as_str()).await?;
-                        self.parse_response(resp).await?;
-                        let parser = CodeforcesParser::new(self.html.as_ref().unwrap());
-                        self.create_problem_dir(&task_url, &parser, false)?;
-                    }
-                } else {
-                    self.create_problem_dir(&url, &parser, true)?;
+                for task_url in problem_url_list.iter() {
+                    let parser = self.fetch_parser(site.as_ref(), task_url).await?;
+                    self.create_problem_dir(task_url, parser.as_ref(), false, refresh)?;
                 }
             }
-            Some(host) => {
-                println!("{} isn't supported yet. X(", host);
-            }
-            _ => {
-                println!("Something wrong happened");
+            None => {
+                let parser = site.parser(&document)?;
+                self.create_problem_dir(&url, parser.as_ref(), true, refresh)?;
             }
         }
         Ok(())
     }
     pub async fn download(&mut self, url: &str) -> Result<(), anyhow::Error> {
         let url = url::Url::parse(url)?;
-        if let Ok(cookie_headers) = util::local_cookie_headers() {
-            self.cookie_headers = cookie_headers;
+        let site = Cpm::find_site(&url)?;
+        let url = site.normalize_url(&url);
+        let sample_test_cases = self.fetch_parser(site.as_ref(), &url).await?.sample_cases();
+        // e.g a contest page, or a problem whose samples aren't in a format the parser knows
+        if sample_test_cases.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No sample cases found in {}. Please make sure it's a problem URL",
+                url
+            ));
         }
-        let resp = self.call_get_request(url.as_str()).await?;
-        self.parse_response(resp).await?;
-
-        let parser = AtCoderParser::new(self.html.as_ref().unwrap());
-        let sample_test_cases = parser.sample_cases();
 
         println!("====== Download Result ======");
 
@@ -255,9 +495,7 @@ impl Cpm {
         Ok(())
     }
     pub fn open(&self) -> Result<(), anyhow::Error> {
-        let file = std::fs::File::open(".problem.json")?;
-        let reader = BufReader::new(file);
-        let info: ProblemInfo = serde_json::from_reader(reader)?;
+        let info = util::load_problem_info(std::path::Path::new("."))?;
         webbrowser::open(&info.url)?;
         Ok(())
     }
@@ -266,63 +504,234 @@ impl Cpm {
         println!("{}", config.root);
         Ok(())
     }
-    pub fn list(&self, all: bool, recent: bool) -> Result<(), anyhow::Error> {
+    pub fn list(&self, options: &ListOptions) -> Result<(), anyhow::Error> {
         let config = load_config()?;
-        let now = Utc::now();
-        for (parent, entry) in walkdir::WalkDir::new(config.root)
-            .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|dir| {
-                let file_name = dir.file_name().to_str().unwrap();
-                file_name == ".problem" || file_name == ".problem.json"
-            })
-            .filter_map(|entry| {
-                entry
-                    .path()
-                    .parent()
-                    .map(|dir| (dir.to_string_lossy().to_string(), entry.clone()))
-            })
-        {
-            let reader = BufReader::new(File::open(entry.path())?);
-            let info = serde_json::from_reader::<_, ProblemInfo>(reader);
-
-            if let Ok(info) = info {
-                let contest_name = info
-                    .contest_name
-                    .chars()
-                    .filter(|&c| c != '\n' && c != '\t')
-                    .collect::<String>();
-                let problem_name = info
-                    .problem_name
-                    .chars()
-                    .filter(|&c| c != '\n' && c != '\t')
-                    .collect::<String>();
-
-                // An old format doesn't support `created_at`. Skip it
-                if recent
-                    && info
-                        .created_at
-                        .map_or(true, |created_at| (now - created_at).num_hours() >= 24)
-                {
-                    continue;
-                }
-
-                if all {
-                    println!("{} {} {}", contest_name, problem_name, parent);
-                } else {
-                    println!("{}", parent);
-                }
-            } else {
-                // An old format doesn't support `created_at`. Skip it
-                if recent {
-                    continue;
-                }
+        let index = load_index(&config.root)?;
+        let mut query = IndexQuery {
+            host: options.host.clone(),
+            contest: options.contest.clone(),
+            status: options.status,
+            ..Default::default()
+        };
+        // An old format doesn't support `created_at`. It's skipped
+        query.created_since = options.since;
+        query.created_until = options.until;
+        if options.recent && query.created_since.is_none() {
+            query.created_since = Some(Utc::now() - chrono::Duration::hours(24));
+        }
+        let mut entries = index
+            .query(&query)
+            .filter(|(_, info)| options.matches_metadata(info))
+            // Removed after it was indexed
+            .filter(|(parent, _)| std::path::Path::new(parent).exists())
+            .collect::<Vec<_>>();
+        listing::sort(&mut entries, options.sort);
+        print!("{}", listing::render(&entries, options.format, options.all));
+        // An old format doesn't support any filters nor structured formats
+        let filtered = options.recent
+            || options.since.is_some()
+            || options.until.is_some()
+            || options.filters_metadata()
+            || options.host.is_some()
+            || options.contest.is_some()
+            || options.status.is_some();
+        if !filtered && options.format == ListFormat::Plain {
+            for parent in index.legacy_dirs.iter() {
                 println!("{}", parent);
             }
         }
         Ok(())
     }
-    pub fn test(&self, command: &str) -> Result<(), anyhow::Error> {
+    pub fn reindex(&self) -> Result<(), anyhow::Error> {
+        let config = load_config()?;
+        let index = WorkspaceIndex::rebuild(std::path::Path::new(&config.root));
+        let index_path = index::index_path();
+        index.save(&index_path)?;
+        println!(
+            "Indexed {} problems in {}",
+            index.problems.len() + index.legacy_dirs.len(),
+            index_path.to_str().unwrap()
+        );
+        Ok(())
+    }
+    // Moves problem directories to the paths of `layout` in the config
+    pub fn migrate_layout(&self, dry_run: bool) -> Result<(), anyhow::Error> {
+        let config = load_config()?;
+        let root = std::path::Path::new(&config.root);
+        let mut index = WorkspaceIndex::rebuild(root);
+        let plan = layout::plan_migration(&index, &config.layout);
+        for (dir, reason) in plan.skipped.iter() {
+            println!("Skipped {}: {}", dir.to_str().unwrap(), reason);
+        }
+        let moves = plan.moves;
+        let root = root.canonicalize()?;
+        for (from, to) in moves.iter() {
+            println!("{} -> {}", from.to_str().unwrap(), to.to_str().unwrap());
+            if dry_run {
+                continue;
+            }
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::rename(from, to)?;
+            if let Some(parent) = from.parent() {
+                layout::remove_empty_parents(parent, &root);
+            }
+            let info = index.problems[from.to_str().unwrap()].clone();
+            index.remove(from);
+            index.upsert(to, info);
+            // Saved each time so that the index follows the tree even if a move fails
+            index.save(&index::index_path())?;
+        }
+        if dry_run {
+            println!("{} directories would be moved", moves.len());
+        } else {
+            index.save(&index::index_path())?;
+            println!("Moved {} directories", moves.len());
+        }
+        Ok(())
+    }
+    // Reads a JSON dump from a local file or a URL
+    async fn read_dump(&self, source: &str) -> Result<String, anyhow::Error> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            println!("Downloading {}", source);
+            let resp = self.client.get(source).send().await?.error_for_status()?;
+            Ok(resp.text().await?)
+        } else {
+            Ok(std::fs::read_to_string(source)?)
+        }
+    }
+    // Imports the AtCoder Problems dumps and adds the metadata to `.problem.json` under the root path.
+    pub async fn sync_metadata(
+        &self,
+        problems: &str,
+        contests: &str,
+        models: &str,
+    ) -> Result<(), anyhow::Error> {
+        let index = MetadataIndex::build(
+            &self.read_dump(problems).await?,
+            &self.read_dump(contests).await?,
+            &self.read_dump(models).await?,
+        )?;
+        let index_path = metadata::index_path();
+        index.save(&index_path)?;
+        println!(
+            "Saved metadata of {} problems in {}",
+            index.problems.len(),
+            index_path.to_str().unwrap()
+        );
+
+        let config = load_config()?;
+        let mut workspace = load_index(&config.root)?;
+        let mut updated = 0;
+        for (dir, indexed_info) in workspace.problems.iter_mut() {
+            // The index may be older than `.problem.json`, which has the latest history and checker
+            let dir = std::path::Path::new(dir);
+            let mut info = match util::load_problem_info(dir) {
+                Ok(info) => info,
+                Err(_) => continue,
+            };
+            if index.enrich(&mut info) {
+                util::create_problem_info_json(info.clone(), dir)?;
+                updated += 1;
+            }
+            *indexed_info = info;
+        }
+        workspace.save(&index::index_path())?;
+        println!("Updated {} problem directories", updated);
+        Ok(())
+    }
+    // `cpm test main.cpp` compiles the source with its language profile if it's newer than the binary.
+    // Otherwise the argument is a command line. `None` means a compile error.
+    fn prepare_command(&self, command: &str) -> Result<Option<TestCommand>, anyhow::Error> {
+        let source = std::path::Path::new(command);
+        let profiles = load_config()
+            .map(|config| config.languages)
+            .unwrap_or_else(|_| language::default_profiles());
+        let profile = match language::find_profile(&profiles, source) {
+            Some(profile) if source.is_file() => profile,
+            _ => {
+                return Ok(Some(TestCommand {
+                    command: util::split_command(command)?,
+                    cap_address_space: true,
+                }))
+            }
+        };
+        if profile.needs_compile(source)? {
+            if let Some(compile_command) = profile.compile_command(source)? {
+                println!("{} {}", "[COMPILE]".cyan(), compile_command.join(" "));
+                let result = command_executor(&compile_command)?.run()?;
+                let messages = [&result.stdout, &result.stderr]
+                    .iter()
+                    .map(|bytes| String::from_utf8_lossy(bytes).trim_end().to_string())
+                    .filter(|message| !message.is_empty())
+                    .collect::<Vec<_>>();
+                if !result.status.success() {
+                    println!("{}", "[CE]".yellow());
+                    messages.iter().for_each(|message| println!("{}", message));
+                    return Ok(None);
+                }
+                // Warnings
+                messages.iter().for_each(|message| println!("{}", message));
+            }
+        }
+        Ok(Some(TestCommand {
+            command: profile.run_command(source)?,
+            cap_address_space: profile.cap_address_space,
+        }))
+    }
+    pub fn test(&self, command: &str, options: &TestOptions) -> Result<(), anyhow::Error> {
+        let command = match self.prepare_command(command)? {
+            Some(command) => command,
+            None => {
+                println!("{} : Compilation Error", "[Wrong Answer]".yellow());
+                return Ok(());
+            }
+        };
+        // The limits of the problem statement are used unless they're given explicitly
+        let info = util::load_problem_info(std::path::Path::new(".")).ok();
+        let time_limit = options
+            .time_limit
+            .or_else(|| {
+                info.as_ref()
+                    .and_then(|info| info.time_limit_ms)
+                    .map(std::time::Duration::from_millis)
+            })
+            .unwrap_or(DEFAULT_TIME_LIMIT);
+        let memory_limit_mb = options
+            .memory_limit_mb
+            .or_else(|| info.as_ref().and_then(|info| info.memory_limit_mb))
+            .unwrap_or(DEFAULT_MEMORY_LIMIT_MB);
+        let memory_limit = memory_limit_bytes(memory_limit_mb)?;
+        let float_tolerance = options
+            .float_tolerance
+            .or_else(|| info.as_ref().and_then(|info| info.float_tolerance));
+        // The command line takes precedence over `.problem.json`
+        let checker_config = if let Some(command) = options.special_judge.as_ref() {
+            CheckerConfig::Special {
+                command: command.clone(),
+            }
+        } else if let Some(name) = options.checker.as_ref() {
+            match name.as_str() {
+                "exact" => CheckerConfig::Exact,
+                "token" => CheckerConfig::Token,
+                "yes_no" => CheckerConfig::YesNo,
+                "unordered_lines" => CheckerConfig::UnorderedLines,
+                "float" => CheckerConfig::Float {
+                    tolerance: float_tolerance.unwrap_or(DEFAULT_FLOAT_TOLERANCE),
+                },
+                _ => return Err(anyhow::anyhow!("Unknown checker: {}", name)),
+            }
+        } else if let Some(tolerance) = options.float_tolerance {
+            CheckerConfig::Float { tolerance }
+        } else if let Some(checker) = info.as_ref().and_then(|info| info.checker.clone()) {
+            checker
+        } else if let Some(tolerance) = float_tolerance {
+            CheckerConfig::Float { tolerance }
+        } else {
+            CheckerConfig::Token
+        };
+        let checker = checker_config.build();
         //current dir is problem?
         let mut sample_case_paths = vec![]; //(input, output)
         if std::path::Path::new(".problem.json").exists()
@@ -354,24 +763,32 @@ impl Cpm {
             }
         }
         sample_case_paths.sort();
+        if let Some(interactor) = options.interactor.as_ref() {
+            return self.test_interactive(
+                &command,
+                &util::split_command(interactor)?,
+                &sample_case_paths,
+                time_limit,
+                memory_limit_mb,
+            );
+        }
         println!("RUNNING TEST CASES...");
+        if checker_config != CheckerConfig::Token {
+            println!("{} {}", "[CHECKER]".cyan(), checker_config);
+        }
         let mut ac_cnt = 0;
         for (input_file_path, output_file_path) in sample_case_paths.iter() {
             println!("-----------------------------------------");
-            let input_file = std::fs::File::open(input_file_path)?;
-            let start = std::time::Instant::now();
-            let commands: Vec<&str> = command.split_whitespace().collect();
-            let command = <&str>::clone(commands.first().expect("No command"));
-            let args: Vec<&str> = commands.into_iter().skip(1).collect();
-            let command_output_child = std::process::Command::new(command)
-                .stdin(input_file)
-                .stdout(std::process::Stdio::piped())
-                .args(args)
-                .spawn()?;
-
-            let output = command_output_child.wait_with_output()?;
-            let elapsed = start.elapsed();
-            let output_string = String::from_utf8(output.stdout).map(|s| s.trim().to_string())?;
+            let input = std::fs::read(input_file_path)?;
+            let result = command
+                .executor(memory_limit)?
+                .stdin(&input)
+                .time_limit(time_limit)
+                .run()?;
+            let elapsed = result.wall_time;
+            // Checkers get the output as it is and normalize it themselves
+            let output_string = String::from_utf8_lossy(&result.stdout).to_string();
+            let stderr_string = String::from_utf8_lossy(&result.stderr).to_string();
 
             println!(
                 "Input: {}",
@@ -384,112 +801,355 @@ impl Cpm {
             let mut sample_output_string = String::new();
             std::fs::File::open(output_file_path)?.read_to_string(&mut sample_output_string)?;
             println!("{} {} ms", "[TIME]".cyan(), elapsed.as_millis());
+            if let Some(peak_memory) = result.peak_memory {
+                println!("{} {} KB", "[MEMORY]".cyan(), peak_memory / 1024);
+            }
 
-            let mut ok = true;
-
-            let mut output_iter = output_string.lines();
-
-            for s in sample_output_string.lines() {
-                if let Some(o) = output_iter.next() {
-                    if o.trim() != s.trim() {
-                        ok = false;
-                    }
-                } else {
-                    ok = false;
-                }
+            if result.timed_out {
+                println!(
+                    "{} exceeded {} ms",
+                    "[TLE]".yellow(),
+                    time_limit.as_millis()
+                );
+                print_stderr(&stderr_string);
+                continue;
             }
-            for rest in output_iter {
-                ok &= rest.chars().all(|c| c.is_whitespace() || c == '\n');
+            if result
+                .peak_memory
+                .is_some_and(|peak_memory| peak_memory > memory_limit)
+            {
+                println!("{} exceeded {} MB", "[MLE]".yellow(), memory_limit_mb);
+                print_stderr(&stderr_string);
+                continue;
             }
+            if !result.status.success() {
+                println!(
+                    "{} {}",
+                    "[RE]".red(),
+                    executor::status_description(&result.status)
+                );
+                // Some runtimes such as the JVM report their own errors on stdout
+                print_stdout(&output_string);
+                print_stderr(&stderr_string);
+                continue;
+            }
+
+            let check_result = checker.check(
+                &String::from_utf8_lossy(&input),
+                &output_string,
+                &sample_output_string,
+            )?;
 
-            if ok {
+            if check_result.accepted {
                 println!("{}", "[OK]".green());
                 ac_cnt += 1;
             } else {
                 println!("{}", "[Wrong Answer]".yellow());
                 //diff
                 println!("The output is");
-                println!("{}", output_string);
+                println!("{}", output_string.trim_end());
                 println!("The judge is");
-                println!("{}", sample_output_string);
+                println!("{}", sample_output_string.trim_end());
+                if let Some(message) = check_result.message {
+                    println!("The checker says");
+                    println!("{}", message);
+                }
+                print_stderr(&stderr_string);
             }
         }
-        let status = if ac_cnt == sample_case_paths.len() {
-            format!(
-                "{} : {} / {}",
-                "[Accept]".green(),
-                ac_cnt,
-                sample_case_paths.len()
-            )
+        print_summary(ac_cnt, sample_case_paths.len());
+        record_test(ac_cnt, sample_case_paths.len())?;
+
+        Ok(())
+    }
+
+    fn test_interactive(
+        &self,
+        command: &TestCommand,
+        interactor: &[String],
+        sample_case_paths: &[(std::path::PathBuf, std::path::PathBuf)],
+        time_limit: std::time::Duration,
+        memory_limit_mb: u64,
+    ) -> Result<(), anyhow::Error> {
+        let memory_limit = memory_limit_bytes(memory_limit_mb)?;
+        // The interactor reads a test case from the file given as its last argument
+        let input_file_paths = if sample_case_paths.is_empty() {
+            vec![None]
         } else {
-            format!(
-                "{} : {} / {}",
-                "[Wrong Answer]".yellow(),
-                ac_cnt,
-                sample_case_paths.len()
-            )
+            sample_case_paths
+                .iter()
+                .map(|(input_file_path, _)| Some(input_file_path))
+                .collect()
         };
-        println!("{}", status);
+        println!("RUNNING INTERACTIVE TEST CASES...");
+        let mut ac_cnt = 0;
+        for input_file_path in input_file_paths.iter() {
+            println!("-----------------------------------------");
+            let solution = command.executor(memory_limit)?.time_limit(time_limit);
+            // The interactor waits for the solution, so it needs a longer limit
+            let mut interactor = command_executor(interactor)?.time_limit(time_limit * 2);
+            if let Some(input_file_path) = input_file_path {
+                println!(
+                    "Input: {}",
+                    input_file_path.file_name().unwrap().to_str().unwrap()
+                );
+                interactor = interactor.arg(input_file_path.to_str().unwrap());
+            }
+            let result = executor::run_interactive(&solution, &interactor)?;
+            println!(
+                "{} {} ms",
+                "[TIME]".cyan(),
+                result.solution.wall_time.as_millis()
+            );
+            if let Some(peak_memory) = result.solution.peak_memory {
+                println!("{} {} KB", "[MEMORY]".cyan(), peak_memory / 1024);
+            }
+            println!("The transcript is");
+            for (direction, text) in result.transcript.iter() {
+                let prefix = match direction {
+                    executor::Direction::SolutionToInteractor => "> ",
+                    executor::Direction::InteractorToSolution => "< ",
+                };
+                text.lines().for_each(|line| println!("{}{}", prefix, line));
+            }
 
+            let solution_stderr = String::from_utf8_lossy(&result.solution.stderr).to_string();
+            let interactor_stderr = String::from_utf8_lossy(&result.interactor.stderr).to_string();
+            if result.solution.timed_out {
+                println!(
+                    "{} exceeded {} ms",
+                    "[TLE]".yellow(),
+                    time_limit.as_millis()
+                );
+            } else if result
+                .solution
+                .peak_memory
+                .is_some_and(|peak_memory| peak_memory > memory_limit)
+            {
+                println!("{} exceeded {} MB", "[MLE]".yellow(), memory_limit_mb);
+            } else if !result.solution.status.success() {
+                println!(
+                    "{} {}",
+                    "[RE]".red(),
+                    executor::status_description(&result.solution.status)
+                );
+            } else if result.interactor.timed_out || !result.interactor.status.success() {
+                println!(
+                    "{} the interactor ended with {}",
+                    "[Wrong Answer]".yellow(),
+                    if result.interactor.timed_out {
+                        "a timeout".to_string()
+                    } else {
+                        executor::status_description(&result.interactor.status)
+                    }
+                );
+            } else {
+                println!("{}", "[OK]".green());
+                ac_cnt += 1;
+                continue;
+            }
+            print_stderr(&solution_stderr);
+            if !interactor_stderr.trim().is_empty() {
+                println!("The interactor's stderr is");
+                println!("{}", interactor_stderr.trim_end());
+            }
+        }
+        print_summary(ac_cnt, input_file_paths.len());
+        record_test(ac_cnt, input_file_paths.len())?;
         Ok(())
     }
 
+    // Submits `file` to the problem of the current directory.
+    // The language is `--language-id`, `--language` or `submit_language` of the file's profile.
+    // It asks to login if there is no valid session.
+    pub async fn submit(
+        &mut self,
+        file: &str,
+        language_id: Option<&str>,
+        language_name: Option<&str>,
+    ) -> Result<(), anyhow::Error> {
+        let info = util::load_problem_info(std::path::Path::new("."))?;
+        let url = url::Url::parse(&info.url)?;
+        let host = url.host_str().unwrap_or_default();
+        let source_path = std::path::Path::new(file);
+        let source = std::fs::read_to_string(source_path)?;
+
+        let language = match (language_id, language_name) {
+            (Some(id), _) => Language::Id(id.to_string()),
+            (None, Some(name)) => Language::Name(name.to_string()),
+            (None, None) => {
+                let profiles = load_config()
+                    .map(|config| config.languages)
+                    .unwrap_or_else(|_| language::default_profiles());
+                let name = language::find_profile(&profiles, source_path)
+                    .and_then(|profile| profile.submit_language_for(host))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown language of {}. Please specify --language or --language-id",
+                            file
+                        )
+                    })?;
+                Language::Name(name.to_string())
+            }
+        };
+
+        // Sites without a login page don't support submit
+        let (site, login_url) = match site::find(&url)
+            .and_then(|site| site.login_url().map(|login_url| (site, login_url)))
+        {
+            Some(site) => site,
+            None => {
+                println!("{} isn't supported yet. X(", host);
+                return Ok(());
+            }
+        };
+        let mut logged_in = false;
+        let submission_url = loop {
+            let result = site.submit(&self.client, &info, &language, &source).await;
+            match result {
+                Err(e) if !logged_in && e.downcast_ref() == Some(&SubmitError::NotLoggedIn) => {
+                    println!("Your session of {} isn't valid. Please login", host);
+                    self.login(login_url).await?;
+                    logged_in = true;
+                }
+                result => break result?,
+            }
+        };
+        println!(
+            "{} {} : {}",
+            "[SUBMITTED]".green(),
+            info.problem_name,
+            submission_url
+        );
+        Ok(())
+    }
+
+    // Shows the latest submission to the problem of the current directory and polls until it's judged.
+    // `all` prints every submission to the problem once.
+    pub async fn status(&mut self, all: bool) -> Result<(), anyhow::Error> {
+        let mut info = util::load_problem_info(std::path::Path::new("."))?;
+        let url = url::Url::parse(&info.url)?;
+        let site = match site::find(&url) {
+            Some(site) => site,
+            None => {
+                println!(
+                    "{} isn't supported yet. X(",
+                    url.host_str().unwrap_or_default()
+                );
+                return Ok(());
+            }
+        };
+        let (status_url, problem_path) = site.submissions_url(&url)?;
+
+        let mut last_printed: Option<SubmissionResult> = None;
+        loop {
+            let resp = self.call_get_request(status_url.as_str()).await?;
+            self.parse_response(resp).await?;
+            let html = self.html.as_ref().unwrap();
+            let results = site
+                .submission_results(html)
+                .into_iter()
+                .filter(|result| result.problem_path == problem_path)
+                .collect::<Vec<_>>();
+            // Judged submissions are saved for `cpm list --status`
+            let mut updated = false;
+            for result in results.iter().filter(|result| result.verdict.is_final()) {
+                updated |= info.record_submission(&result.id, &result.verdict);
+            }
+            if updated {
+                save_problem_info(info.clone(), std::path::Path::new("."))?;
+            }
+            if all {
+                results.iter().for_each(print_submission_result);
+                return Ok(());
+            }
+            let latest = match results.into_iter().next() {
+                Some(latest) => latest,
+                None => {
+                    println!("No submissions to {} in {}", info.problem_name, status_url);
+                    return Ok(());
+                }
+            };
+            if last_printed.as_ref() != Some(&latest) {
+                print_submission_result(&latest);
+            }
+            if latest.verdict.is_final() {
+                return Ok(());
+            }
+            last_printed = Some(latest);
+            tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+        }
+    }
+
     pub async fn login(&mut self, url: &str) -> Result<(), anyhow::Error> {
         let url = url::Url::parse(url)?;
-        let resp = self.call_get_request(url.as_str()).await?;
-        self.parse_response(resp).await?;
-        let parser = AtCoderParser::new(self.html.as_ref().unwrap());
-        //necessary information and parameters to login AtCoder
-        let csrf_token = parser.csrf_token().unwrap();
-        let (username, password) = util::username_and_password();
-        let params = {
-            let mut params = std::collections::HashMap::new();
-            params.insert("username", username);
-            params.insert("password", password);
-            params.insert("csrf_token", csrf_token);
-            params
+        let site = match site::find(&url).filter(|site| site.login_url().is_some()) {
+            Some(site) => site,
+            None => {
+                println!(
+                    "{} isn't supported yet. X(",
+                    url.host_str().unwrap_or_default()
+                );
+                return Ok(());
+            }
         };
-        //make a post request and try to login
-        let resp = self.call_post_request(url.as_str(), &params).await?;
+        let (username, password) = util::username_and_password();
+        site.login(&self.client, &url, &username, &password).await?;
         //save your cookie in your local
-        util::save_cookie_in_local(&resp)?;
+        let path = session::cookie_jar_path();
+        self.cookie_jar.save(&path)?;
+        println!("SAVED YOUR COOKIE IN {}", path.to_str().unwrap());
         Ok(())
     }
-
-    async fn call_post_request(
-        &self,
-        url: &str,
-        params: &std::collections::HashMap<&str, String>,
-    ) -> Result<reqwest::Response, reqwest::Error> {
-        let resp = self
-            .client
-            .post(url)
-            .headers(self.cookie_headers.clone())
-            .form(params)
-            .send()
-            .await?;
-        Ok(resp)
+    // Checks whether the saved session of each site is still valid by opening a page.
+    pub async fn login_status(&mut self) -> Result<(), anyhow::Error> {
+        for site in site::sites() {
+            let url = match site.home_url() {
+                Some(url) => url,
+                None => continue,
+            };
+            let host = url::Url::parse(url)?
+                .host_str()
+                .unwrap_or_default()
+                .to_string();
+            let cookies = self.cookie_jar.cookies_of(&host);
+            if cookies.is_empty() {
+                println!("{} Not logged in", format!("[{}]", host).yellow());
+                continue;
+            }
+            let resp = self.call_get_request(url).await?;
+            self.parse_response(resp).await?;
+            let html = self.html.as_ref().unwrap();
+            let user = site.user_name(html);
+            // The session lasts until its cookie expiring first
+            let expires = cookies.iter().filter_map(|cookie| cookie.expires).min();
+            match (user, expires) {
+                (Some(user), Some(expires)) => println!(
+                    "{} Logged in as {} (expires at {})",
+                    format!("[{}]", host).green(),
+                    user,
+                    expires
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                ),
+                (Some(user), None) => {
+                    println!("{} Logged in as {}", format!("[{}]", host).green(), user)
+                }
+                (None, _) => println!(
+                    "{} The session has expired. Please login again",
+                    format!("[{}]", host).yellow()
+                ),
+            }
+        }
+        Ok(())
     }
     async fn call_get_request(&self, url: &str) -> Result<reqwest::Response, reqwest::Error> {
-        let resp = self
-            .client
-            .get(url)
-            .headers(self.cookie_headers.clone())
-            .send()
-            .await?;
+        let resp = self.client.get(url).send().await?;
         Ok(resp)
     }
 
     async fn parse_response(&mut self, response: reqwest::Response) -> Result<(), anyhow::Error> {
-        //cookie
-        let mut cookie_headers = HeaderMap::new();
-        response.cookies().for_each(|cookie| {
-            cookie_headers.insert(
-                COOKIE,
-                HeaderValue::from_str(&format!("{}={}", cookie.name(), cookie.value())).unwrap(),
-            );
-        });
-        self.cookie_headers = cookie_headers;
+        //cookies are stored in the cookie jar of the client
         self.html = Some(response.text().await?);
         Ok(())
     }
@@ -512,7 +1172,13 @@ Example:
     cpm download https://atcoder.jp/contests/agc035/tasks/agc035_a
 
     //Login
-    cpm login",
+    cpm login
+    cpm login https://codeforces.com/enter
+    cpm login --status
+
+    //Submit
+    cpm submit main.cpp
+    cpm status",
         )
         .subcommand(
             clap::SubCommand::with_name(&SubCommand::Init.value()).about("Initialize config file"),
@@ -522,9 +1188,11 @@ Example:
                 .about("Create a new directory from URL under root path")
                 .arg(
                     clap::Arg::with_name("url")
-                        .help("A URL of problem")
+                        .help("A URL of problem, or of a contest or a problem set to get all of its problems")
                         .required(true),
-                ),
+                )
+                .arg_from_usage("--refresh 'Show the changes of sample cases when the directory already exists'")
+                .arg_from_usage("--section=[SECTION] 'Get only a section of a problem set (e.g. \"Sorting and Searching\" of https://cses.fi/problemset/). Same as `#sorting-and-searching` at the end of the URL'"),
         )
         .subcommand(
             clap::SubCommand::with_name(&SubCommand::Open.value()).about("Open the problem page"),
@@ -540,16 +1208,61 @@ Example:
         )
         .subcommand(
             clap::SubCommand::with_name(&SubCommand::Login.value())
-                .about("Login AtCoder and save session in your local")
-                .arg(clap::Arg::with_name("url").help("A login URL of AtCoder")),
+                .about("Login AtCoder or Codeforces and save session in your local")
+                .arg(clap::Arg::with_name("url").help(
+                    "A login URL (default: https://atcoder.jp/login). e.g https://codeforces.com/enter",
+                ))
+                .arg_from_usage("-s, --status 'Show whether the saved session of each site is still valid'"),
         )
         .subcommand(clap::SubCommand::with_name(&SubCommand::Root.value()).about("Show root path"))
+        .subcommand(
+            clap::SubCommand::with_name(&SubCommand::Reindex.value())
+                .about("Rebuild the index of problem directories used by `cpm list`"),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(&SubCommand::MigrateLayout.value())
+                .about("Move problem directories to the paths of `layout` in the config")
+                .arg_from_usage("-n, --dry-run 'Print the moves without moving directories'"),
+        )
         .subcommand(
             clap::SubCommand::with_name(&SubCommand::List.value())
                 .about("List local directories under root path")
                 .arg_from_usage("-a, --all 'Print problem's information(contest name, problem name, directory).")
                 .arg_from_usage(
                     "-r, --recent 'Print only recent problems (less than 24 hours).'",
+                )
+                .arg_from_usage("--host=[HOST] 'Print only problems of HOST (e.g. atcoder.jp)'")
+                .arg_from_usage("--since=[DATE] 'Print only problems created on or after DATE (YYYY-MM-DD)'")
+                .arg_from_usage("--until=[DATE] 'Print only problems created on or before DATE (YYYY-MM-DD)'")
+                .arg_from_usage("--contest=[CONTEST] 'Print only problems of a contest ID (e.g. abc154) or a contest name. `*` and `?` are wildcards (e.g. 'abc15*')'")
+                .arg(
+                    clap::Arg::with_name("status")
+                        .long("status")
+                        .takes_value(true)
+                        .possible_values(["unsolved", "attempted", "solved"])
+                        .help("Print only problems of the status. `solved` means an accepted submission is seen by `cpm status`"),
+                )
+                .arg_from_usage("--min-difficulty=[N] 'Print only problems whose AtCoder Problems difficulty is at least N'")
+                .arg_from_usage("--max-difficulty=[N] 'Print only problems whose AtCoder Problems difficulty is at most N'")
+                .arg_from_usage("--min-point=[POINT] 'Print only problems worth at least POINT'")
+                .arg_from_usage("--max-point=[POINT] 'Print only problems worth at most POINT'")
+                .arg_from_usage("--contest-since=[DATE] 'Print only problems of contests held on or after DATE (YYYY-MM-DD)'")
+                .arg_from_usage("--contest-until=[DATE] 'Print only problems of contests held on or before DATE (YYYY-MM-DD)'")
+                .arg(
+                    clap::Arg::with_name("sort")
+                        .long("sort")
+                        .takes_value(true)
+                        .possible_values(["path", "created_at"])
+                        .default_value("path")
+                        .help("Sort problems by the directory or by when they were downloaded (oldest first)"),
+                )
+                .arg(
+                    clap::Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(["plain", "table", "json", "csv"])
+                        .default_value("plain")
+                        .help("Output format. Directories of an old format are printed only in `plain`"),
                 ),
         )
         .subcommand(
@@ -557,15 +1270,68 @@ Example:
                 .about("Test sample test cases")
                 .arg(
                     clap::Arg::with_name("command")
-                        .help("An execute command run for test cases")
+                        .help("An execute command run for test cases, or a source file to build with its language profile (e.g. main.cpp)")
+                        .required(true),
+                )
+                .arg_from_usage("-t, --time-limit=[SECONDS] 'Time limit per test case in seconds (default: the problem's limit or 2 seconds)'")
+                .arg_from_usage("-m, --memory-limit=[MB] 'Memory limit per test case in MB (default: the problem's limit or 1024 MB)'")
+                .arg_from_usage("--float-tolerance=[EPS] 'Accept numbers within an absolute or relative error (default: the problem's tolerance)'")
+                .arg(
+                    clap::Arg::with_name("checker")
+                        .long("checker")
+                        .takes_value(true)
+                        .possible_values(["exact", "token", "yes_no", "unordered_lines", "float"])
+                        .help("How outputs are judged (default: the problem's checker or token)"),
+                )
+                .arg_from_usage("--special-judge=[COMMAND] 'A checker program called as `COMMAND input output answer`'")
+                .arg_from_usage("--interactor=[COMMAND] 'Test an interactive problem. The interactor is called as `COMMAND input` and its exit code is the verdict'"),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(&SubCommand::Submit.value())
+                .about("Submit a source file to the problem of the current directory")
+                .arg(
+                    clap::Arg::with_name("file")
+                        .help("A source file to submit")
                         .required(true),
+                )
+                .arg_from_usage("-l, --language=[NAME] 'A part of the language name on the submit page (default: the language profile's submit_language)'")
+                .arg_from_usage("--language-id=[ID] 'A language ID on the submit page (e.g. 5001)'"),
+        )
+        .subcommand(
+            clap::SubCommand::with_name(&SubCommand::SyncMetadata.value())
+                .about("Import problem metadata (difficulty, point, contest date) from AtCoder Problems")
+                .arg(
+                    clap::Arg::with_name("problems")
+                        .long("problems")
+                        .takes_value(true)
+                        .default_value(metadata::PROBLEMS_URL)
+                        .help("A file or URL of problems.json or merged-problems.json"),
+                )
+                .arg(
+                    clap::Arg::with_name("contests")
+                        .long("contests")
+                        .takes_value(true)
+                        .default_value(metadata::CONTESTS_URL)
+                        .help("A file or URL of contests.json"),
+                )
+                .arg(
+                    clap::Arg::with_name("models")
+                        .long("models")
+                        .takes_value(true)
+                        .default_value(metadata::MODELS_URL)
+                        .help("A file or URL of problem-models.json"),
                 ),
         )
+        .subcommand(
+            clap::SubCommand::with_name(&SubCommand::Status.value())
+                .about("Show the verdict of the latest submission to the problem of the current directory")
+                .arg_from_usage("-a, --all 'Print all submissions to the problem without waiting for the judge'"),
+        )
         .get_matches();
     //run sub commands
     let mut cpm = Cpm::new();
     if matches
-        .subcommand_matches(&SubCommand::Init.value())
+        .subcommand_matches(SubCommand::Init.value())
         .is_some()
     {
         match cpm.init() {
@@ -577,7 +1343,7 @@ Example:
         }
     }
     if matches
-        .subcommand_matches(&SubCommand::Open.value())
+        .subcommand_matches(SubCommand::Open.value())
         .is_some()
     {
         match cpm.open() {
@@ -590,7 +1356,7 @@ Example:
     }
 
     if matches
-        .subcommand_matches(&SubCommand::Root.value())
+        .subcommand_matches(SubCommand::Root.value())
         .is_some()
     {
         match cpm.root() {
@@ -602,8 +1368,15 @@ Example:
         }
     }
 
-    if let Some(matched) = matches.subcommand_matches(&SubCommand::Get.value()) {
-        match cpm.get(matched.value_of("url").unwrap()).await {
+    if let Some(matched) = matches.subcommand_matches(SubCommand::Get.value()) {
+        match cpm
+            .get(
+                matched.value_of("url").unwrap(),
+                matched.is_present("refresh"),
+                matched.value_of("section"),
+            )
+            .await
+        {
             Ok(_) => std::process::exit(0),
             Err(e) => {
                 println!("{:?}", e);
@@ -612,7 +1385,7 @@ Example:
         }
     }
 
-    if let Some(matched) = matches.subcommand_matches(&SubCommand::Download.value()) {
+    if let Some(matched) = matches.subcommand_matches(SubCommand::Download.value()) {
         match cpm.download(matched.value_of("url").unwrap()).await {
             Ok(_) => {
                 std::process::exit(0);
@@ -623,7 +1396,16 @@ Example:
             }
         }
     }
-    if let Some(matched) = matches.subcommand_matches(&SubCommand::Login.value()) {
+    if let Some(matched) = matches.subcommand_matches(SubCommand::Login.value()) {
+        if matched.is_present("status") {
+            match cpm.login_status().await {
+                Ok(_) => std::process::exit(0),
+                Err(e) => {
+                    println!("{:?}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
         match cpm
             .login(
                 matched
@@ -642,8 +1424,72 @@ Example:
         }
     }
 
-    if let Some(args) = matches.subcommand_matches(&SubCommand::List.value()) {
-        match cpm.list(args.is_present("all"), args.is_present("recent")) {
+    if matches
+        .subcommand_matches(SubCommand::Reindex.value())
+        .is_some()
+    {
+        match cpm.reindex() {
+            Ok(_) => std::process::exit(0),
+            Err(e) => {
+                println!("{:?}", e);
+                std::process::exit(1)
+            }
+        }
+    }
+    if let Some(args) = matches.subcommand_matches(SubCommand::MigrateLayout.value()) {
+        match cpm.migrate_layout(args.is_present("dry-run")) {
+            Ok(_) => std::process::exit(0),
+            Err(e) => {
+                println!("{:?}", e);
+                std::process::exit(1)
+            }
+        }
+    }
+    if let Some(args) = matches.subcommand_matches(SubCommand::List.value()) {
+        fn parse_or_exit<T: std::str::FromStr>(args: &clap::ArgMatches, name: &str) -> Option<T> {
+            args.value_of(name).map(|value| {
+                value.parse::<T>().unwrap_or_else(|_| {
+                    println!("Invalid {}: {}", name, value);
+                    std::process::exit(1);
+                })
+            })
+        }
+        // A date in the local time zone
+        let date = |name: &str, time: chrono::NaiveTime| {
+            parse_or_exit::<chrono::NaiveDate>(args, name).and_then(|date| {
+                chrono::Local
+                    .from_local_datetime(&date.and_time(time))
+                    .earliest()
+                    .map(|date| date.with_timezone(&Utc))
+            })
+        };
+        let options = ListOptions {
+            all: args.is_present("all"),
+            recent: args.is_present("recent"),
+            since: date("since", chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap()),
+            until: date(
+                "until",
+                chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap(),
+            ),
+            host: args.value_of("host").map(|host| host.to_string()),
+            contest: args.value_of("contest").map(|contest| contest.to_string()),
+            status: parse_or_exit(args, "status"),
+            min_difficulty: parse_or_exit(args, "min-difficulty"),
+            max_difficulty: parse_or_exit(args, "max-difficulty"),
+            min_point: parse_or_exit(args, "min-point"),
+            max_point: parse_or_exit(args, "max-point"),
+            contest_since: date(
+                "contest-since",
+                chrono::NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+            ),
+            contest_until: date(
+                "contest-until",
+                chrono::NaiveTime::from_hms_opt(23, 59, 59).unwrap(),
+            ),
+            sort: parse_or_exit(args, "sort").unwrap(),
+            format: parse_or_exit(args, "format").unwrap(),
+        };
+        match cpm.list(&options) {
             Ok(_) => {
                 std::process::exit(0);
             }
@@ -653,11 +1499,106 @@ Example:
             }
         }
     }
-    if let Some(matched) = matches.subcommand_matches(&SubCommand::Test.value()) {
-        match cpm.test(matched.value_of("command").unwrap()) {
+    if let Some(matched) = matches.subcommand_matches(SubCommand::Test.value()) {
+        let time_limit = match matched.value_of("time-limit").map(|s| s.parse::<f64>()) {
+            Some(Ok(seconds)) if seconds > 0.0 => Some(std::time::Duration::from_secs_f64(seconds)),
+            Some(_) => {
+                println!(
+                    "Invalid time limit: {}",
+                    matched.value_of("time-limit").unwrap()
+                );
+                std::process::exit(1);
+            }
+            None => None,
+        };
+        let memory_limit_mb = match matched.value_of("memory-limit").map(|s| s.parse::<u64>()) {
+            Some(Ok(mb)) if mb > 0 => Some(mb),
+            Some(_) => {
+                println!(
+                    "Invalid memory limit: {}",
+                    matched.value_of("memory-limit").unwrap()
+                );
+                std::process::exit(1);
+            }
+            None => None,
+        };
+        let float_tolerance = match matched
+            .value_of("float-tolerance")
+            .map(|s| s.parse::<f64>())
+        {
+            Some(Ok(eps)) if eps >= 0.0 => Some(eps),
+            Some(_) => {
+                println!(
+                    "Invalid float tolerance: {}",
+                    matched.value_of("float-tolerance").unwrap()
+                );
+                std::process::exit(1);
+            }
+            None => None,
+        };
+        let options = TestOptions {
+            time_limit,
+            memory_limit_mb,
+            float_tolerance,
+            checker: matched.value_of("checker").map(|s| s.to_string()),
+            special_judge: matched.value_of("special-judge").map(|s| s.to_string()),
+            interactor: matched.value_of("interactor").map(|s| s.to_string()),
+        };
+        match cpm.test(matched.value_of("command").unwrap(), &options) {
+            Ok(_) => {
+                std::process::exit(0);
+            }
+            Err(e) => {
+                println!("{:?}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(matched) = matches.subcommand_matches(SubCommand::SyncMetadata.value()) {
+        match cpm
+            .sync_metadata(
+                matched.value_of("problems").unwrap(),
+                matched.value_of("contests").unwrap(),
+                matched.value_of("models").unwrap(),
+            )
+            .await
+        {
+            Ok(_) => {
+                std::process::exit(0);
+            }
+            Err(e) => {
+                println!("{:?}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(matched) = matches.subcommand_matches(SubCommand::Status.value()) {
+        match cpm.status(matched.is_present("all")).await {
+            Ok(_) => {
+                std::process::exit(0);
+            }
+            Err(e) => {
+                println!("{:?}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(matched) = matches.subcommand_matches(SubCommand::Submit.value()) {
+        match cpm
+            .submit(
+                matched.value_of("file").unwrap(),
+                matched.value_of("language-id"),
+                matched.value_of("language"),
+            )
+            .await
+        {
             Ok(_) => {
                 std::process::exit(0);
             }
+            Err(e) if e.downcast_ref() == Some(&SubmitError::DuplicateSubmission) => {
+                println!("{} {}", "[DUPLICATE]".yellow(), e);
+                std::process::exit(1);
+            }
             Err(e) => {
                 println!("{:?}", e);
                 std::process::exit(1);
@@ -0,0 +1,238 @@
+//! Problem metadata from the AtCoder Problems (kenkoooo) JSON dumps.
+
+use crate::util::{self, ProblemInfo};
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+pub const PROBLEMS_URL: &str = "https://kenkoooo.com/atcoder/resources/merged-problems.json";
+pub const CONTESTS_URL: &str = "https://kenkoooo.com/atcoder/resources/contests.json";
+pub const MODELS_URL: &str = "https://kenkoooo.com/atcoder/resources/problem-models.json";
+
+// An item of `problems.json` or `merged-problems.json`. Only the latter has `point`
+#[derive(Deserialize)]
+struct ProblemEntry {
+    id: String,
+    contest_id: String,
+    name: String,
+    #[serde(default)]
+    point: Option<f64>,
+}
+
+// An item of `contests.json`
+#[derive(Deserialize)]
+struct ContestEntry {
+    id: String,
+    start_epoch_second: i64,
+    title: String,
+}
+
+// A value of `problem-models.json`
+#[derive(Deserialize)]
+struct ProblemModel {
+    #[serde(default)]
+    difficulty: Option<f64>,
+    #[serde(default)]
+    is_experimental: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProblemMetadata {
+    pub contest_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contest_title: Option<String>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub point: Option<f64>,
+    /// The difficulty shown on AtCoder Problems, i.e. clipped to be positive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<i64>,
+    /// The difficulty was estimated from few contestants
+    #[serde(default)]
+    pub is_experimental: bool,
+    #[serde(default, with = "ts_seconds_option")]
+    pub contest_start: Option<DateTime<Utc>>,
+}
+
+/// Metadata of AtCoder problems keyed by problem ID. e.g `abc154_a`
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct MetadataIndex {
+    #[serde(default, with = "ts_seconds_option")]
+    pub synced_at: Option<DateTime<Utc>>,
+    pub problems: BTreeMap<String, ProblemMetadata>,
+}
+
+/// Low difficulties are mapped to positive values in the same way as AtCoder Problems.
+pub fn clip_difficulty(difficulty: f64) -> i64 {
+    if difficulty >= 400.0 {
+        difficulty.round() as i64
+    } else {
+        (400.0 / (1.0 - difficulty / 400.0).exp()).round() as i64
+    }
+}
+
+/// The problem ID of an AtCoder problem URL.
+/// e.g `https://atcoder.jp/contests/abc154/tasks/abc154_a` -> `abc154_a`
+pub fn problem_id(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    if url.host_str() != Some("atcoder.jp") {
+        return None;
+    }
+    let paths = url.path_segments()?.collect::<Vec<_>>();
+    match paths.as_slice() {
+        ["contests", _, "tasks", id] => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+impl MetadataIndex {
+    /// Joins the contents of `problems.json` (or `merged-problems.json`), `contests.json`
+    /// and `problem-models.json`.
+    pub fn build(
+        problems_json: &str,
+        contests_json: &str,
+        models_json: &str,
+    ) -> Result<MetadataIndex, anyhow::Error> {
+        let problems: Vec<ProblemEntry> = serde_json::from_str(problems_json)?;
+        let contests = serde_json::from_str::<Vec<ContestEntry>>(contests_json)?
+            .into_iter()
+            .map(|contest| (contest.id.clone(), contest))
+            .collect::<HashMap<_, _>>();
+        let models: HashMap<String, ProblemModel> = serde_json::from_str(models_json)?;
+
+        let problems = problems
+            .into_iter()
+            .map(|problem| {
+                let contest = contests.get(&problem.contest_id);
+                let model = models.get(&problem.id);
+                let metadata = ProblemMetadata {
+                    contest_title: contest.map(|contest| contest.title.clone()),
+                    contest_start: contest
+                        .filter(|contest| contest.start_epoch_second > 0)
+                        .and_then(|contest| {
+                            Utc.timestamp_opt(contest.start_epoch_second, 0).single()
+                        }),
+                    contest_id: problem.contest_id,
+                    name: problem.name,
+                    point: problem.point,
+                    difficulty: model
+                        .and_then(|model| model.difficulty)
+                        .map(clip_difficulty),
+                    is_experimental: model.is_some_and(|model| model.is_experimental),
+                };
+                (problem.id, metadata)
+            })
+            .collect();
+        Ok(MetadataIndex {
+            synced_at: Some(Utc::now()),
+            problems,
+        })
+    }
+    /// Loads an index from `path`. A missing file is an empty index.
+    pub fn load(path: &Path) -> Result<MetadataIndex, anyhow::Error> {
+        util::load_json_or_default(path)
+    }
+    pub fn save(&self, path: &Path) -> Result<(), anyhow::Error> {
+        util::save_json(path, self)
+    }
+    /// Metadata of the problem at `url` if it's an AtCoder problem in the index
+    pub fn find(&self, url: &str) -> Option<&ProblemMetadata> {
+        self.problems.get(&problem_id(url)?)
+    }
+    /// Copies the metadata of `info`'s problem into it. Returns whether `info` was changed.
+    pub fn enrich(&self, info: &mut ProblemInfo) -> bool {
+        let metadata = match self.find(&info.url) {
+            Some(metadata) => metadata,
+            None => return false,
+        };
+        let before = (info.difficulty, info.point, info.contest_start);
+        info.difficulty = metadata.difficulty;
+        info.point = metadata.point;
+        info.contest_start = metadata.contest_start;
+        before != (info.difficulty, info.point, info.contest_start)
+    }
+}
+
+/// $HOME/.cpm/atcoder_problems.json
+pub fn index_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap()
+        .join(".cpm")
+        .join("atcoder_problems.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clip_difficulty, problem_id, MetadataIndex};
+    use chrono::{TimeZone, Utc};
+
+    fn index() -> MetadataIndex {
+        MetadataIndex::build(
+            include_str!("../tests/fixtures/atcoder_problems/merged-problems.json"),
+            include_str!("../tests/fixtures/atcoder_problems/contests.json"),
+            include_str!("../tests/fixtures/atcoder_problems/problem-models.json"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build() {
+        let index = index();
+        assert_eq!(index.problems.len(), 4);
+
+        let problem = &index.problems["abc154_e"];
+        assert_eq!(problem.name, "Almost Everywhere Zero");
+        assert_eq!(
+            problem.contest_title,
+            Some("AtCoder Beginner Contest 154".to_string())
+        );
+        assert_eq!(problem.point, Some(500.0));
+        assert_eq!(problem.difficulty, Some(1536));
+        assert_eq!(
+            problem.contest_start,
+            Some(Utc.timestamp_opt(1581249600, 0).unwrap())
+        );
+
+        assert_eq!(index.problems["abc154_a"].difficulty, Some(8));
+        let practice = &index.problems["practice_1"];
+        assert_eq!(practice.point, None);
+        assert_eq!(practice.difficulty, None);
+        assert_eq!(practice.contest_start, None);
+    }
+
+    #[test]
+    fn test_clip_difficulty() {
+        assert_eq!(clip_difficulty(1536.0), 1536);
+        assert_eq!(clip_difficulty(400.0), 400);
+        assert_eq!(clip_difficulty(0.0), 147);
+        assert_eq!(clip_difficulty(-1145.0), 8);
+    }
+
+    #[test]
+    fn test_problem_id() {
+        assert_eq!(
+            problem_id("https://atcoder.jp/contests/abc154/tasks/abc154_a"),
+            Some("abc154_a".to_string())
+        );
+        assert_eq!(problem_id("https://atcoder.jp/contests/abc154"), None);
+        assert_eq!(
+            problem_id("https://codeforces.com/contest/4/problem/A"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_enrich() {
+        let index = index();
+        let mut info: crate::util::ProblemInfo = serde_json::from_str(
+            r#"{"url": "https://atcoder.jp/contests/abc163/tasks/abc163_a", "contest_name": "AtCoder Beginner Contest 163", "problem_name": "A - Circle Pond", "created_at": null}"#,
+        )
+        .unwrap();
+        assert!(index.enrich(&mut info));
+        assert_eq!(info.point, Some(100.0));
+        assert_eq!(info.difficulty, Some(10));
+        assert!(!index.enrich(&mut info));
+    }
+}
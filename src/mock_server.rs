@@ -0,0 +1,148 @@
+//! A tiny HTTP server serving recorded pages for tests.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+#[derive(Clone)]
+pub struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    pub fn ok(body: &str) -> Response {
+        Response {
+            status: 200,
+            headers: vec![],
+            body: body.to_string(),
+        }
+    }
+    pub fn redirect(location: &str) -> Response {
+        Response {
+            status: 302,
+            headers: vec![("Location".to_string(), location.to_string())],
+            body: "".to_string(),
+        }
+    }
+    pub fn header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: String,
+    /// A path with its query. e.g `/contests/abc154/submit?taskScreenName=abc154_a`
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+pub struct MockServer {
+    pub base_url: url::Url,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Serves `routes` keyed by a method and a path without a query. e.g `("GET", "/login")`
+    pub async fn start(routes: Vec<((&str, &str), Response)>) -> MockServer {
+        let routes = routes
+            .into_iter()
+            .map(|((method, path), response)| ((method.to_string(), path.to_string()), response))
+            .collect::<HashMap<_, _>>();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url =
+            url::Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        let requests = Arc::new(Mutex::new(vec![]));
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(_) => return,
+                };
+                let request = match read_request(&mut socket).await {
+                    Some(request) => request,
+                    None => continue,
+                };
+                let path = request.path.split('?').next().unwrap().to_string();
+                let response = routes
+                    .get(&(request.method.clone(), path))
+                    .cloned()
+                    .unwrap_or(Response {
+                        status: 404,
+                        headers: vec![],
+                        body: "Not Found".to_string(),
+                    });
+                recorded.lock().unwrap().push(request);
+
+                let mut raw = format!(
+                    "HTTP/1.1 {} MOCK\r\nContent-Length: {}\r\nConnection: close\r\n",
+                    response.status,
+                    response.body.len()
+                );
+                for (name, value) in response.headers.iter() {
+                    raw.push_str(&format!("{}: {}\r\n", name, value));
+                }
+                raw.push_str("\r\n");
+                raw.push_str(&response.body);
+                let _ = socket.write_all(raw.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+        MockServer { base_url, requests }
+    }
+
+    pub fn url(&self, path: &str) -> url::Url {
+        self.base_url.join(path).unwrap()
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn read_request(socket: &mut tokio::net::TcpStream) -> Option<Request> {
+    let mut buf = vec![];
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let len = socket.read(&mut chunk).await.ok()?;
+        if len == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..len]);
+        if let Some(pos) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect::<HashMap<_, _>>();
+    let content_length = headers
+        .get("content-length")
+        .and_then(|length| length.parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < header_end + content_length {
+        let len = socket.read(&mut chunk).await.ok()?;
+        if len == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..len]);
+    }
+    let body = String::from_utf8_lossy(&buf[header_end..]).to_string();
+    Some(Request {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
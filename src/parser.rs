@@ -2,4 +2,147 @@ pub trait Parser {
     fn problem_name(&self) -> Option<String>;
     fn contest_name(&self) -> Option<String>;
     fn sample_cases(&self) -> Vec<(String, String)>;
+    /// Time limit in milliseconds
+    fn time_limit_ms(&self) -> Option<u64>;
+    /// Memory limit in megabytes
+    fn memory_limit_mb(&self) -> Option<u64>;
+    /// An absolute or relative error allowed in the output, if the statement mentions one
+    fn float_tolerance(&self) -> Option<f64>;
+}
+
+// Splits the first number in `text` and the word that follows it.
+// e.g "time limit per test2 seconds" -> (2.0, "seconds")
+fn number_and_unit(text: &str) -> Option<(f64, String)> {
+    let start = text.find(|c: char| c.is_ascii_digit())?;
+    let rest = &text[start..];
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(rest.len());
+    let number = rest[..end].parse::<f64>().ok()?;
+    let unit = rest[end..]
+        .trim_start()
+        .chars()
+        .take_while(|c| !c.is_whitespace() && *c != '/' && *c != ')')
+        .collect::<String>()
+        .to_lowercase();
+    Some((number, unit))
+}
+
+/// Parses a time limit like `2 sec`, `2.5 seconds`, `500 ms` or `2秒` into milliseconds.
+pub fn parse_time_limit_ms(text: &str) -> Option<u64> {
+    let (number, unit) = number_and_unit(text)?;
+    let scale = if unit.starts_with("ms") || unit.starts_with("millisecond") || unit == "ミリ秒"
+    {
+        1.0
+    } else if unit.starts_with('s') || unit.starts_with('秒') {
+        1000.0
+    } else {
+        return None;
+    };
+    Some((number * scale).round() as u64)
+}
+
+/// Parses a memory limit like `1024 MB`, `256 megabytes` or `1 GB` into megabytes.
+pub fn parse_memory_limit_mb(text: &str) -> Option<u64> {
+    let (number, unit) = number_and_unit(text)?;
+    let scale = if unit.starts_with("kb") || unit.starts_with("kib") || unit.starts_with("kilo") {
+        1.0 / 1024.0
+    } else if unit.starts_with("mb") || unit.starts_with("mib") || unit.starts_with("mega") {
+        1.0
+    } else if unit.starts_with("gb") || unit.starts_with("gib") || unit.starts_with("giga") {
+        1024.0
+    } else {
+        return None;
+    };
+    Some((number * scale).round() as u64)
+}
+
+/// Finds an allowed error in a problem statement.
+/// e.g "absolute or relative error at most 10^{-6}", "絶対誤差または相対誤差が 10^{-6} 以下"
+pub fn parse_float_tolerance(statement: &str) -> Option<f64> {
+    const KEYWORDS: [&str; 3] = ["error", "誤差", "precision"];
+    // How far from the keyword the exponent can be
+    const WINDOW: usize = 150;
+
+    let statement = statement.to_lowercase();
+    KEYWORDS.iter().find_map(|keyword| {
+        statement.match_indices(keyword).find_map(|(idx, _)| {
+            let window = statement[idx..].chars().take(WINDOW).collect::<String>();
+            window
+                .match_indices("10^")
+                .find_map(|(idx, _)| parse_negative_exponent(&window[idx + "10^".len()..]))
+                .map(|exponent| 10f64.powi(-exponent))
+        })
+    })
+}
+
+// "{-6}", "-6", "{−6}" or "{- 6}" -> 6
+fn parse_negative_exponent(text: &str) -> Option<i32> {
+    let text = text.trim_start_matches(|c: char| c == '{' || c.is_whitespace());
+    let text = text
+        .strip_prefix('-')
+        .or_else(|| text.strip_prefix('−'))?
+        .trim_start();
+    let digits = text
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>();
+    digits.parse::<i32>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_float_tolerance, parse_memory_limit_mb, parse_time_limit_ms};
+
+    #[test]
+    fn test_parse_time_limit_ms() {
+        assert_eq!(parse_time_limit_ms("2 sec"), Some(2000));
+        assert_eq!(parse_time_limit_ms("5.25 sec"), Some(5250));
+        assert_eq!(
+            parse_time_limit_ms("time limit per test2 seconds"),
+            Some(2000)
+        );
+        assert_eq!(parse_time_limit_ms("1 second"), Some(1000));
+        assert_eq!(parse_time_limit_ms("500 ms"), Some(500));
+        assert_eq!(parse_time_limit_ms("2秒"), Some(2000));
+        assert_eq!(parse_time_limit_ms("no limit"), None);
+    }
+
+    #[test]
+    fn test_parse_memory_limit_mb() {
+        assert_eq!(parse_memory_limit_mb("1024 MB"), Some(1024));
+        assert_eq!(
+            parse_memory_limit_mb("memory limit per test256 megabytes"),
+            Some(256)
+        );
+        assert_eq!(parse_memory_limit_mb("1 GB"), Some(1024));
+        assert_eq!(parse_memory_limit_mb("65536 KB"), Some(64));
+        assert_eq!(parse_memory_limit_mb("2 sec"), None);
+    }
+
+    #[test]
+    fn test_parse_float_tolerance() {
+        assert_eq!(
+            parse_float_tolerance(
+                "Your output is considered correct if and only if its absolute or relative error from our answer is at most 10^{-2}."
+            ),
+            Some(1e-2)
+        );
+        assert_eq!(
+            parse_float_tolerance(
+                "想定解答との絶対誤差または相対誤差が 10^{-6} 以下であれば正解として扱われる。"
+            ),
+            Some(1e-6)
+        );
+        assert_eq!(
+            parse_float_tolerance(
+                "Your answer will be considered correct if its absolute or relative error does not exceed $$$10^{-9}$$$."
+            ),
+            Some(1e-9)
+        );
+        assert_eq!(
+            parse_float_tolerance("1 \\leq N \\leq 10^5. Print the answer modulo 10^9+7."),
+            None
+        );
+    }
 }
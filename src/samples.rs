@@ -0,0 +1,236 @@
+//! Sample cases saved in the `sample` directory of a problem.
+
+use crate::util;
+use std::path::{Path, PathBuf};
+
+/// Whether `cpm get` created the file. Other files are custom cases added by a user.
+/// e.g `sample_input_1.txt`, `sample_00_out.txt` of an old format
+fn is_downloaded(file_name: &str) -> bool {
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    let name = match file_name.strip_suffix(".txt") {
+        Some(name) => name,
+        None => return false,
+    };
+    if let Some(number) = name
+        .strip_prefix("sample_input_")
+        .or_else(|| name.strip_prefix("sample_output_"))
+    {
+        return is_number(number);
+    }
+    if let Some(number) = name.strip_prefix("sample_").and_then(|name| {
+        name.strip_suffix("_in")
+            .or_else(|| name.strip_suffix("_out"))
+    }) {
+        return is_number(number);
+    }
+    false
+}
+
+/// Sample cases in `dir` in the order of their numbers
+pub fn load_sample_cases(dir: &Path) -> Result<Vec<(String, String)>, anyhow::Error> {
+    let mut samples = vec![];
+    loop {
+        let case = samples.len() + 1;
+        let input = dir.join(format!("sample_input_{}.txt", case));
+        let output = dir.join(format!("sample_output_{}.txt", case));
+        if !input.exists() || !output.exists() {
+            break;
+        }
+        samples.push((
+            std::fs::read_to_string(input)?,
+            std::fs::read_to_string(output)?,
+        ));
+    }
+    Ok(samples)
+}
+
+/// A line diff of `old` and `new`. Lines start with `-` (removed), `+` (added) or ` ` (kept).
+pub fn diff_lines(old: &str, new: &str) -> Vec<String> {
+    let old = old.lines().collect::<Vec<_>>();
+    let new = new.lines().collect::<Vec<_>>();
+    // lcs[i][j]: the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut lines = vec![];
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!(" {}", old[i]));
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(format!("-{}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+{}", new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// A readable diff of sample cases. It's empty if nothing changed.
+pub fn diff_sample_cases(old: &[(String, String)], new: &[(String, String)]) -> Vec<String> {
+    let empty = (String::new(), String::new());
+    let mut lines = vec![];
+    for case in 0..old.len().max(new.len()) {
+        let (old_input, old_output) = old.get(case).unwrap_or(&empty);
+        let (new_input, new_output) = new.get(case).unwrap_or(&empty);
+        if old_input == new_input && old_output == new_output {
+            continue;
+        }
+        let title = if case >= old.len() {
+            "added"
+        } else if case >= new.len() {
+            "removed"
+        } else {
+            "changed"
+        };
+        lines.push(format!("=== Sample Test Case {} ({}) ===", case + 1, title));
+        for (name, old, new) in [
+            ("Input", old_input, new_input),
+            ("Output", old_output, new_output),
+        ] {
+            if old != new {
+                lines.push(format!("{}:", name));
+                lines.extend(diff_lines(old, new));
+            }
+        }
+    }
+    lines
+}
+
+/// Replaces the downloaded sample cases in `dir` with `samples`. Custom cases are kept.
+///
+/// The new directory is built next to `dir` and swapped with it, so an error never leaves
+/// a half-written directory.
+pub fn rewrite_sample_dir(dir: &Path, samples: &[(String, String)]) -> Result<(), anyhow::Error> {
+    let sibling = |suffix: &str| -> PathBuf {
+        let mut name = dir.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}-{}", suffix, std::process::id()));
+        dir.with_file_name(name)
+    };
+    let new_dir = sibling("new");
+    let old_dir = sibling("old");
+    if new_dir.exists() {
+        std::fs::remove_dir_all(&new_dir)?;
+    }
+    util::create_sample_test_files(samples, new_dir.to_str())?;
+    if dir.exists() {
+        for entry in walkdir::WalkDir::new(dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let relative = entry.path().strip_prefix(dir)?;
+            let dest = new_dir.join(relative);
+            if entry.file_type().is_dir() {
+                std::fs::create_dir_all(dest)?;
+            } else if entry.depth() > 1 || !is_downloaded(&entry.file_name().to_string_lossy()) {
+                std::fs::copy(entry.path(), dest)?;
+            }
+        }
+        std::fs::rename(dir, &old_dir)?;
+    }
+    if let Err(e) = std::fs::rename(&new_dir, dir) {
+        // Put the old one back
+        if old_dir.exists() {
+            std::fs::rename(&old_dir, dir)?;
+        }
+        return Err(e.into());
+    }
+    if old_dir.exists() {
+        std::fs::remove_dir_all(old_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        diff_lines, diff_sample_cases, is_downloaded, load_sample_cases, rewrite_sample_dir,
+    };
+    use crate::util::TempDir;
+
+    fn cases(cases: &[(&str, &str)]) -> Vec<(String, String)> {
+        cases
+            .iter()
+            .map(|(input, output)| (input.to_string(), output.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_is_downloaded() {
+        assert!(is_downloaded("sample_input_1.txt"));
+        assert!(is_downloaded("sample_output_12.txt"));
+        assert!(is_downloaded("sample_00_in.txt"));
+        assert!(!is_downloaded("sample_input_x.txt"));
+        assert!(!is_downloaded("custom_input_1.txt"));
+        assert!(!is_downloaded("sample_input_1.txt.bak"));
+    }
+
+    #[test]
+    fn test_diff_lines() {
+        assert_eq!(
+            diff_lines("3\n1 2 3\nYes\n", "3\n1 2 4\nYes\n"),
+            vec![" 3", "-1 2 3", "+1 2 4", " Yes"]
+        );
+        assert_eq!(diff_lines("", "1\n"), vec!["+1"]);
+        assert_eq!(diff_lines("a\nb\n", "b\n"), vec!["-a", " b"]);
+    }
+
+    #[test]
+    fn test_diff_sample_cases() {
+        let old = cases(&[("1\n", "Yes\n"), ("2\n", "No\n")]);
+        assert!(diff_sample_cases(&old, &old).is_empty());
+        let new = cases(&[("1\n", "Yes\n"), ("2\n", "Yes\n"), ("3\n", "No\n")]);
+        assert_eq!(
+            diff_sample_cases(&old, &new),
+            vec![
+                "=== Sample Test Case 2 (changed) ===",
+                "Output:",
+                "-No",
+                "+Yes",
+                "=== Sample Test Case 3 (added) ===",
+                "Input:",
+                "+3",
+                "Output:",
+                "+No",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_sample_dir() {
+        let temp_dir = TempDir::new("samples");
+        let root = temp_dir.path();
+        let dir = root.join("sample");
+        rewrite_sample_dir(
+            &dir,
+            &cases(&[("1\n", "a\n"), ("2\n", "b\n"), ("3\n", "c\n")]),
+        )
+        .unwrap();
+        std::fs::write(dir.join("custom_input_1.txt"), "4\n").unwrap();
+        std::fs::write(dir.join("sample_00_in.txt"), "old\n").unwrap();
+
+        let new = cases(&[("1\n", "x\n")]);
+        rewrite_sample_dir(&dir, &new).unwrap();
+        assert_eq!(load_sample_cases(&dir).unwrap(), new);
+        assert!(!dir.join("sample_input_2.txt").exists());
+        assert!(!dir.join("sample_00_in.txt").exists());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("custom_input_1.txt")).unwrap(),
+            "4\n"
+        );
+        // No temporary directories are left
+        assert_eq!(std::fs::read_dir(root).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::util;
+use chrono::serde::ts_seconds_option;
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::header::HeaderValue;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    pub path: String,
+    /// `None` means a session cookie
+    #[serde(with = "ts_seconds_option")]
+    pub expires: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub secure: bool,
+    /// Sent only to the exact domain because the cookie has no `Domain` attribute
+    #[serde(default)]
+    pub host_only: bool,
+}
+
+impl StoredCookie {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+    // e.g `/contests` matches `/contests/abc154` but not `/contestsabc`
+    fn matches_path(&self, path: &str) -> bool {
+        path == self.path
+            || (path.starts_with(&self.path)
+                && (self.path.ends_with('/') || path[self.path.len()..].starts_with('/')))
+    }
+}
+
+/// Cookies keyed by domain, saved as JSON. e.g `{"atcoder.jp": [{"name": "REVEL_SESSION", ...}]}`
+///
+/// This is plugged into `reqwest::Client` with `cookie_provider`.
+#[derive(Default)]
+pub struct CookieJar {
+    domains: Mutex<BTreeMap<String, Vec<StoredCookie>>>,
+}
+
+impl CookieJar {
+    /// Loads a jar from `path`. A missing file is an empty jar.
+    pub fn load(path: &Path) -> Result<CookieJar, anyhow::Error> {
+        Ok(CookieJar {
+            domains: Mutex::new(util::load_json_or_default(path)?),
+        })
+    }
+    /// Saves cookies except expired ones. Session cookies are kept because
+    /// a login should last across commands.
+    pub fn save(&self, path: &Path) -> Result<(), anyhow::Error> {
+        let now = Utc::now();
+        let mut domains = self.domains.lock().unwrap().clone();
+        domains
+            .values_mut()
+            .for_each(|cookies| cookies.retain(|cookie| !cookie.is_expired(now)));
+        domains.retain(|_, cookies| !cookies.is_empty());
+
+        util::save_json(path, &domains)
+    }
+    /// Cookies stored for `domain` which haven't expired
+    pub fn cookies_of(&self, domain: &str) -> Vec<StoredCookie> {
+        let now = Utc::now();
+        self.domains
+            .lock()
+            .unwrap()
+            .get(domain)
+            .map(|cookies| {
+                cookies
+                    .iter()
+                    .filter(|cookie| !cookie.is_expired(now))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    /// Adds cookies of a `Cookie` header value like `a=b; c=d` as session cookies.
+    /// This is for cookie files saved by old versions.
+    pub fn import_header(&self, domain: &str, header: &str) {
+        let mut domains = self.domains.lock().unwrap();
+        let cookies = domains.entry(domain.to_string()).or_default();
+        for pair in header.split(';') {
+            if let Some((name, value)) = pair.trim().split_once('=') {
+                let cookie = StoredCookie {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                    path: "/".to_string(),
+                    expires: None,
+                    secure: false,
+                    host_only: true,
+                };
+                upsert(cookies, cookie);
+            }
+        }
+    }
+}
+
+// Replaces a cookie with the same name and path
+fn upsert(cookies: &mut Vec<StoredCookie>, cookie: StoredCookie) {
+    cookies.retain(|stored| !(stored.name == cookie.name && stored.path == cookie.path));
+    cookies.push(cookie);
+}
+
+// The directory of a request path. e.g `/contests/abc154/submit` -> `/contests/abc154`
+fn default_path(url: &url::Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(idx) => url.path()[..idx].to_string(),
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &url::Url) {
+        let host = match url.host_str() {
+            Some(host) => host.to_string(),
+            None => return,
+        };
+        let now = Utc::now();
+        let mut domains = self.domains.lock().unwrap();
+        for header in cookie_headers {
+            let cookie = match header
+                .to_str()
+                .ok()
+                .and_then(|header| ::cookie::Cookie::parse(header.to_string()).ok())
+            {
+                Some(cookie) => cookie,
+                None => continue,
+            };
+            // Max-Age takes precedence over Expires
+            let expires = match (cookie.max_age(), cookie.expires_datetime()) {
+                (Some(max_age), _) => {
+                    Some(now + chrono::Duration::seconds(max_age.whole_seconds()))
+                }
+                (None, Some(expires)) => Utc.timestamp_opt(expires.unix_timestamp(), 0).single(),
+                (None, None) => None,
+            };
+            let (domain, host_only) = match cookie.domain() {
+                Some(domain) => (domain.trim_start_matches('.').to_lowercase(), false),
+                None => (host.clone(), true),
+            };
+            // A server can't set cookies of another site
+            if domain != host && !host.ends_with(&format!(".{}", domain)) {
+                continue;
+            }
+            let stored = StoredCookie {
+                name: cookie.name().to_string(),
+                value: cookie.value().to_string(),
+                path: cookie
+                    .path()
+                    .map(|path| path.to_string())
+                    .unwrap_or_else(|| default_path(url)),
+                expires,
+                secure: cookie.secure().unwrap_or(false),
+                host_only,
+            };
+            let cookies = domains.entry(domain).or_default();
+            let expired = stored.is_expired(now);
+            upsert(cookies, stored);
+            // Servers delete a cookie by an expiration date in the past
+            if expired {
+                cookies.retain(|cookie| !cookie.is_expired(now));
+            }
+        }
+    }
+
+    fn cookies(&self, url: &url::Url) -> Option<HeaderValue> {
+        let host = url.host_str()?;
+        let now = Utc::now();
+        let domains = self.domains.lock().unwrap();
+        let header = domains
+            .iter()
+            .filter(|(domain, _)| host == *domain || host.ends_with(&format!(".{}", domain)))
+            .flat_map(|(domain, cookies)| {
+                cookies.iter().filter(move |cookie| {
+                    (!cookie.host_only || host == domain)
+                        && (!cookie.secure || url.scheme() == "https")
+                        && !cookie.is_expired(now)
+                        && cookie.matches_path(url.path())
+                })
+            })
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if header.is_empty() {
+            None
+        } else {
+            HeaderValue::from_str(&header).ok()
+        }
+    }
+}
+
+/// $HOME/.cpm/cookies.json
+pub fn cookie_jar_path() -> PathBuf {
+    dirs::home_dir().unwrap().join(".cpm").join("cookies.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CookieJar;
+    use crate::util::TempDir;
+    use reqwest::cookie::CookieStore;
+    use reqwest::header::HeaderValue;
+
+    fn set(jar: &CookieJar, url: &str, headers: &[&str]) {
+        let headers = headers
+            .iter()
+            .map(|header| HeaderValue::from_str(header).unwrap())
+            .collect::<Vec<_>>();
+        jar.set_cookies(&mut headers.iter(), &url::Url::parse(url).unwrap());
+    }
+    fn get(jar: &CookieJar, url: &str) -> Option<String> {
+        jar.cookies(&url::Url::parse(url).unwrap())
+            .map(|header| header.to_str().unwrap().to_string())
+    }
+
+    #[test]
+    fn test_domain_and_path() {
+        let jar = CookieJar::default();
+        set(
+            &jar,
+            "https://atcoder.jp/login",
+            &[
+                "REVEL_SESSION=abc; Path=/; Max-Age=3600; HttpOnly; Secure",
+                "language=ja; Path=/contests",
+            ],
+        );
+        set(
+            &jar,
+            "https://codeforces.com/enter",
+            &[
+                "JSESSIONID=xyz; Path=/",
+                "39ce7=def; Domain=.codeforces.com",
+            ],
+        );
+        assert_eq!(
+            get(&jar, "https://atcoder.jp/contests/abc154"),
+            Some("REVEL_SESSION=abc; language=ja".to_string())
+        );
+        assert_eq!(
+            get(&jar, "https://atcoder.jp/home"),
+            Some("REVEL_SESSION=abc".to_string())
+        );
+        // Secure cookies aren't sent over http
+        assert_eq!(get(&jar, "http://atcoder.jp/home"), None);
+        assert_eq!(
+            get(&jar, "https://m1.codeforces.com/"),
+            Some("39ce7=def".to_string())
+        );
+        assert_eq!(jar.cookies_of("codeforces.com").len(), 2);
+        assert_eq!(jar.cookies_of("m1.codeforces.com").len(), 0);
+        assert_eq!(get(&jar, "https://example.com/"), None);
+    }
+
+    #[test]
+    fn test_expiry() {
+        let jar = CookieJar::default();
+        set(
+            &jar,
+            "https://atcoder.jp/",
+            &[
+                "a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+                "b=2; Expires=Fri, 01 Jan 2100 00:00:00 GMT",
+                "c=3",
+            ],
+        );
+        assert_eq!(
+            get(&jar, "https://atcoder.jp/"),
+            Some("b=2; c=3".to_string())
+        );
+        // Deleted by an expiration date in the past
+        set(&jar, "https://atcoder.jp/", &["b=; Max-Age=0"]);
+        assert_eq!(get(&jar, "https://atcoder.jp/"), Some("c=3".to_string()));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let temp_dir = TempDir::new("cookies");
+        let path = temp_dir.path().join("cookies.json");
+        let jar = CookieJar::default();
+        set(
+            &jar,
+            "https://atcoder.jp/",
+            &[
+                "REVEL_SESSION=abc; Max-Age=3600",
+                "expired=1; Max-Age=0",
+                "session=1",
+            ],
+        );
+        jar.save(&path).unwrap();
+        let loaded = CookieJar::load(&path).unwrap();
+        assert_eq!(
+            get(&loaded, "https://atcoder.jp/"),
+            Some("REVEL_SESSION=abc; session=1".to_string())
+        );
+        assert!(loaded.cookies_of("atcoder.jp")[0].expires.is_some());
+    }
+}
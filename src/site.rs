@@ -0,0 +1,220 @@
+//! Sites which cpm can get problems from. Every command finds the site of a URL in `sites()`,
+//! so a new site only has to implement `Site` and be listed there.
+
+use crate::parser::Parser;
+use crate::submit::{Language, SubmissionResult};
+use crate::util::ProblemInfo;
+use std::future::Future;
+use std::pin::Pin;
+
+/// A future returned by an async method of `Site`
+pub type SiteFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, anyhow::Error>> + 'a>>;
+
+pub trait Site {
+    /// A readable name. e.g `AtCoder`
+    fn name(&self) -> &'static str;
+    /// Whether problems on `host` belong to the site
+    fn matches(&self, host: &str) -> bool;
+    /// The URL to fetch instead of `url`. e.g a contest page to its task list
+    fn normalize_url(&self, url: &url::Url) -> url::Url {
+        url.clone()
+    }
+    /// Fetches the document which `parser` and `problem_url_list` read. It's the page by default.
+    fn fetch_document<'a>(
+        &'a self,
+        client: &'a reqwest::Client,
+        url: &'a url::Url,
+    ) -> SiteFuture<'a, String> {
+        Box::pin(async move { Ok(client.get(url.clone()).send().await?.text().await?) })
+    }
+    /// A parser of the problem in `document`
+    fn parser(&self, document: &str) -> Result<Box<dyn Parser>, anyhow::Error>;
+    /// Problems of a list page such as a contest. `None` if `url` is a problem.
+    fn problem_url_list(&self, _url: &url::Url, _document: &str) -> Option<Vec<url::Url>> {
+        None
+    }
+    /// The contest ID and the problem index of a problem URL, used by the layout and `cpm list --contest`.
+    /// e.g `("abc154", "a")` on AtCoder, `("4", "A")` of `/problemset/problem/4/A` on Codeforces.
+    /// Problems outside contests are grouped by their URL path. e.g `("problems", "hello")` on Kattis
+    fn contest_and_index(&self, url: &url::Url) -> Option<(String, String)>;
+    /// The page to login. `None` if `login` and `submit` aren't supported.
+    fn login_url(&self) -> Option<&'static str> {
+        None
+    }
+    /// Logs in with the form on `login_url`. The session is kept in the cookie store of `client`,
+    /// which sends it on later requests such as `submit`.
+    fn login<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        _login_url: &'a url::Url,
+        _username: &'a str,
+        _password: &'a str,
+    ) -> SiteFuture<'a, ()> {
+        Box::pin(async move { Err(anyhow::anyhow!("{} doesn't support login", self.name())) })
+    }
+    /// A page which shows the user name with a valid session. `None` if `cpm login --status` skips the site.
+    fn home_url(&self) -> Option<&'static str> {
+        None
+    }
+    /// The user name on `home_url`. `None` if the page is for a guest.
+    fn user_name(&self, _document: &str) -> Option<String> {
+        None
+    }
+    /// Submits `source` to the problem of `info` and returns the URL of the submissions.
+    /// It fails with `SubmitError::NotLoggedIn` without a valid session.
+    fn submit<'a>(
+        &'a self,
+        _client: &'a reqwest::Client,
+        _info: &'a ProblemInfo,
+        _language: &'a Language,
+        _source: &'a str,
+    ) -> SiteFuture<'a, url::Url> {
+        Box::pin(async move { Err(anyhow::anyhow!("{} doesn't support submit", self.name())) })
+    }
+    /// The page of your submissions to the problem `url` and the problem path which rows of
+    /// `submission_results` have. e.g `/contests/abc154/submissions/me` and `/contests/abc154/tasks/abc154_a`
+    fn submissions_url(&self, _url: &url::Url) -> Result<(url::Url, String), anyhow::Error> {
+        Err(anyhow::anyhow!("{} doesn't support status", self.name()))
+    }
+    /// Rows of the page of `submissions_url`, newest first
+    fn submission_results(&self, _document: &str) -> Vec<SubmissionResult> {
+        vec![]
+    }
+}
+
+/// Every supported site
+pub fn sites() -> Vec<Box<dyn Site>> {
+    vec![
+        Box::new(crate::atcoder::AtCoderSite),
+        Box::new(crate::codeforces::CodeforcesSite),
+        Box::new(crate::yukicoder::YukicoderSite),
+        Box::new(crate::aoj::AojSite),
+        Box::new(crate::cses::CsesSite),
+        Box::new(crate::kattis::KattisSite),
+    ]
+}
+
+/// The site of `url`
+pub fn find(url: &url::Url) -> Option<Box<dyn Site>> {
+    let host = url.host_str()?;
+    sites().into_iter().find(|site| site.matches(host))
+}
+
+/// Joins relative paths of a list page to `url`
+pub(crate) fn join_all(url: &url::Url, paths: &[String]) -> Vec<url::Url> {
+    paths
+        .iter()
+        .filter_map(|path| url.join(path).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find;
+
+    #[test]
+    fn test_find() {
+        let name = |url: &str| find(&url::Url::parse(url).unwrap()).map(|site| site.name());
+        assert_eq!(
+            name("https://atcoder.jp/contests/abc154/tasks/abc154_a"),
+            Some("AtCoder")
+        );
+        assert_eq!(
+            name("https://codeforces.com/contest/4/problem/A"),
+            Some("Codeforces")
+        );
+        assert_eq!(
+            name("https://yukicoder.me/problems/no/1"),
+            Some("yukicoder")
+        );
+        assert_eq!(
+            name("http://judge.u-aizu.ac.jp/onlinejudge/description.jsp?id=0001"),
+            Some("AOJ")
+        );
+        assert_eq!(name("https://cses.fi/problemset/task/1068"), Some("CSES"));
+        assert_eq!(
+            name("https://open.kattis.com/problems/hello"),
+            Some("Kattis")
+        );
+        assert_eq!(name("https://example.com/"), None);
+    }
+
+    #[test]
+    fn test_normalize_url() {
+        let normalize = |url: &str| {
+            let url = url::Url::parse(url).unwrap();
+            find(&url).unwrap().normalize_url(&url).to_string()
+        };
+        assert_eq!(
+            normalize("https://atcoder.jp/contests/abc154"),
+            "https://atcoder.jp/contests/abc154/tasks"
+        );
+        assert_eq!(
+            normalize("https://atcoder.jp/contests/abc154/tasks/abc154_a"),
+            "https://atcoder.jp/contests/abc154/tasks/abc154_a"
+        );
+        assert_eq!(
+            normalize("https://codeforces.com/contest/4"),
+            "https://codeforces.com/contest/4"
+        );
+    }
+
+    #[test]
+    fn test_problem_metadata() {
+        let site = |url: &str| {
+            let url = url::Url::parse(url).unwrap();
+            (find(&url).unwrap(), url)
+        };
+        let (codeforces, url) = site("https://codeforces.com/problemset/problem/4/A");
+        assert_eq!(
+            codeforces.contest_and_index(&url),
+            Some(("4".to_string(), "A".to_string()))
+        );
+        let (status_url, problem_path) = codeforces.submissions_url(&url).unwrap();
+        assert_eq!(status_url.as_str(), "https://codeforces.com/contest/4/my");
+        assert_eq!(problem_path, "/contest/4/problem/A");
+
+        let (atcoder, url) = site("https://atcoder.jp/contests/abc154/tasks/abc154_a");
+        assert_eq!(
+            atcoder.contest_and_index(&url),
+            Some(("abc154".to_string(), "a".to_string()))
+        );
+        let (status_url, problem_path) = atcoder.submissions_url(&url).unwrap();
+        assert_eq!(
+            status_url.as_str(),
+            "https://atcoder.jp/contests/abc154/submissions/me"
+        );
+        assert_eq!(problem_path, "/contests/abc154/tasks/abc154_a");
+
+        let contest_and_index = |url: &str| {
+            let (site, url) = site(url);
+            site.contest_and_index(&url)
+                .map(|(contest, index)| format!("{}/{}", contest, index))
+        };
+        assert_eq!(
+            contest_and_index("https://yukicoder.me/problems/no/1"),
+            Some("no/1".to_string())
+        );
+        assert_eq!(
+            contest_and_index("https://onlinejudge.u-aizu.ac.jp/problems/ALDS1_1_B"),
+            Some("ALDS1_1/B".to_string())
+        );
+        assert_eq!(
+            contest_and_index("http://judge.u-aizu.ac.jp/onlinejudge/description.jsp?id=0001"),
+            Some("problems/0001".to_string())
+        );
+        assert_eq!(
+            contest_and_index("https://cses.fi/problemset/task/1068"),
+            Some("problemset/1068".to_string())
+        );
+        assert_eq!(
+            contest_and_index("https://open.kattis.com/problems/hello"),
+            Some("problems/hello".to_string())
+        );
+        assert_eq!(contest_and_index("https://cses.fi/problemset/"), None);
+
+        let (yukicoder, url) = site("https://yukicoder.me/problems/no/1");
+        assert!(yukicoder.submissions_url(&url).is_err());
+        assert_eq!(yukicoder.home_url(), None);
+    }
+}
@@ -0,0 +1,244 @@
+/// An item of the language `<select>` on a submit page
+#[derive(Clone, Debug, PartialEq)]
+pub struct LanguageOption {
+    pub id: String,
+    pub name: String,
+}
+
+/// Which language a source is submitted as
+#[derive(Clone, Debug, PartialEq)]
+pub enum Language {
+    /// The value of an `<option>`. e.g `5001`
+    Id(String),
+    /// The name of an `<option>` or a word of it, ignoring case. e.g `C++` matches `C++ 20 (gcc 12.2)`
+    /// and `Java` matches `Java (OpenJDK 17)` but not `JavaScript (Node.js 18.16.1)`.
+    /// The whole name is preferred, then the name before its `(`, then the first option containing it.
+    Name(String),
+}
+
+impl Language {
+    pub fn resolve(&self, options: &[LanguageOption]) -> Option<String> {
+        match self {
+            Language::Id(id) => options
+                .iter()
+                .find(|option| &option.id == id)
+                .map(|option| option.id.clone()),
+            Language::Name(name) => {
+                let name = name.trim().to_lowercase();
+                let names = options
+                    .iter()
+                    .map(|option| option.name.to_lowercase())
+                    .collect::<Vec<_>>();
+                names
+                    .iter()
+                    .position(|option| option.trim() == name)
+                    .or_else(|| {
+                        names.iter().position(|option| {
+                            option.split('(').next().unwrap_or_default().trim() == name
+                        })
+                    })
+                    .or_else(|| names.iter().position(|option| contains_word(option, &name)))
+                    .map(|i| options[i].id.clone())
+            }
+        }
+    }
+}
+
+// Whether `name` is in `text` and not a part of a longer word.
+// A version can follow it, so `g++` is in `gnu g++17 7.3.0` but `java` isn't in `javascript` and `c` isn't in `c++`.
+fn contains_word(text: &str, name: &str) -> bool {
+    if name.is_empty() {
+        return false;
+    }
+    text.match_indices(name).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + name.len()..].chars().next();
+        before.is_none_or(|c| !c.is_alphanumeric())
+            && after.is_none_or(|c| !c.is_alphabetic() && c != '+' && c != '#')
+    })
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Language::Id(id) => write!(f, "language id {}", id),
+            Language::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Why a site didn't accept a submission
+#[derive(Clone, Debug, PartialEq)]
+pub enum SubmitError {
+    /// The submit page isn't available without a valid session
+    NotLoggedIn,
+    /// Codeforces rejects a source which is the same as a previous submission
+    DuplicateSubmission,
+    /// Other errors shown on the submit page
+    Rejected(String),
+}
+
+impl std::fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmitError::NotLoggedIn => write!(f, "Not logged in"),
+            SubmitError::DuplicateSubmission => {
+                write!(f, "You have submitted exactly the same code before")
+            }
+            SubmitError::Rejected(message) => write!(f, "The submission was rejected: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for SubmitError {}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer,
+    TimeLimitExceeded,
+    MemoryLimitExceeded,
+    RuntimeError,
+    CompilationError,
+    /// Waiting for judge or being judged
+    Judging,
+    /// A verdict we don't know. e.g `OLE`, `IE`
+    Other(String),
+}
+
+impl Verdict {
+    /// Parses an abbreviation shown on AtCoder. e.g `AC`, `WJ`
+    pub fn from_abbreviation(abbreviation: &str) -> Verdict {
+        match abbreviation {
+            "AC" => Verdict::Accepted,
+            "WA" => Verdict::WrongAnswer,
+            "TLE" => Verdict::TimeLimitExceeded,
+            "MLE" => Verdict::MemoryLimitExceeded,
+            "RE" => Verdict::RuntimeError,
+            "CE" => Verdict::CompilationError,
+            "WJ" | "WR" | "Judging" => Verdict::Judging,
+            other => Verdict::Other(other.to_string()),
+        }
+    }
+    pub fn is_final(&self) -> bool {
+        *self != Verdict::Judging
+    }
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Accepted => write!(f, "AC"),
+            Verdict::WrongAnswer => write!(f, "WA"),
+            Verdict::TimeLimitExceeded => write!(f, "TLE"),
+            Verdict::MemoryLimitExceeded => write!(f, "MLE"),
+            Verdict::RuntimeError => write!(f, "RE"),
+            Verdict::CompilationError => write!(f, "CE"),
+            Verdict::Judging => write!(f, "WJ"),
+            Verdict::Other(verdict) => write!(f, "{}", verdict),
+        }
+    }
+}
+
+/// A row of a submission list such as "My Submissions" on AtCoder
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubmissionResult {
+    pub id: String,
+    /// A path of the problem. e.g `/contests/abc154/tasks/abc154_a`
+    pub problem_path: String,
+    pub problem_name: String,
+    pub language: String,
+    pub verdict: Verdict,
+    /// The test case being judged or the first failed one, e.g `3` of `3/12` on AtCoder
+    /// and "Wrong answer on test 3" on Codeforces
+    pub test_case: Option<u64>,
+    /// The number of test cases if it's shown
+    pub total_test_cases: Option<u64>,
+    /// Execution time in milliseconds
+    pub exec_time_ms: Option<u64>,
+    /// Memory usage in kilobytes
+    pub memory_kb: Option<u64>,
+}
+
+// "6 ms" -> 6, "2104 KB" -> 2104, "62\u{a0}ms" -> 62
+pub(crate) fn leading_number(text: &str) -> Option<u64> {
+    let digits = text
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect::<String>();
+    digits.parse::<u64>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{leading_number, Language, LanguageOption, Verdict};
+
+    #[test]
+    fn test_resolve() {
+        let options = vec![
+            LanguageOption {
+                id: "5001".to_string(),
+                name: "C++ 20 (gcc 12.2)".to_string(),
+            },
+            LanguageOption {
+                id: "5055".to_string(),
+                name: "Python (CPython 3.11.4)".to_string(),
+            },
+        ];
+        assert_eq!(
+            Language::Name("python".to_string()).resolve(&options),
+            Some("5055".to_string())
+        );
+        assert_eq!(
+            Language::Id("5001".to_string()).resolve(&options),
+            Some("5001".to_string())
+        );
+        assert_eq!(Language::Id("1".to_string()).resolve(&options), None);
+        assert_eq!(Language::Name("Rust".to_string()).resolve(&options), None);
+        assert_eq!(Language::Name("C".to_string()).resolve(&options), None);
+    }
+
+    #[test]
+    fn test_resolve_whole_name() {
+        let options = vec![
+            LanguageOption {
+                id: "5019".to_string(),
+                name: "JavaScript (Node.js 18.16.1)".to_string(),
+            },
+            LanguageOption {
+                id: "5005".to_string(),
+                name: "Java (OpenJDK 17)".to_string(),
+            },
+            LanguageOption {
+                id: "5054".to_string(),
+                name: "GNU G++17 7.3.0".to_string(),
+            },
+            LanguageOption {
+                id: "5017".to_string(),
+                name: "C".to_string(),
+            },
+        ];
+        let resolve = |name: &str| Language::Name(name.to_string()).resolve(&options);
+        assert_eq!(resolve("Java"), Some("5005".to_string()));
+        assert_eq!(resolve("javascript"), Some("5019".to_string()));
+        assert_eq!(resolve("G++"), Some("5054".to_string()));
+        assert_eq!(resolve("C"), Some("5017".to_string()));
+        assert_eq!(resolve("Jav"), None);
+    }
+
+    #[test]
+    fn test_verdict() {
+        assert_eq!(Verdict::from_abbreviation("AC"), Verdict::Accepted);
+        assert_eq!(Verdict::from_abbreviation("WJ"), Verdict::Judging);
+        assert!(!Verdict::Judging.is_final());
+        assert_eq!(
+            Verdict::from_abbreviation("OLE"),
+            Verdict::Other("OLE".to_string())
+        );
+        assert_eq!(Verdict::TimeLimitExceeded.to_string(), "TLE");
+        assert_eq!(leading_number("62\u{a0}ms"), Some(62));
+        assert_eq!(leading_number(" 2104 KB"), Some(2104));
+        assert_eq!(leading_number("-"), None);
+    }
+}
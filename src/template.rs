@@ -0,0 +1,175 @@
+//! Source templates copied into a new problem directory.
+//!
+//! Templates are kept per language, e.g `~/.config/cpm/templates/cpp/main.cpp`.
+//! Text files can contain these placeholders:
+//! - `{{problem_name}}`
+//! - `{{contest_name}}`
+//! - `{{url}}`
+//! - `{{created_at}}`: e.g `2020-02-09 21:00:00` in the local time zone
+
+use crate::util::ProblemInfo;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TemplateConfig {
+    /// Defaults to `~/.config/cpm/templates`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    /// Languages whose templates are copied. Empty means every language in `dir`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub languages: Vec<String>,
+}
+
+impl TemplateConfig {
+    pub fn dir(&self) -> PathBuf {
+        match &self.dir {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::home_dir()
+                .unwrap()
+                .join(".config")
+                .join("cpm")
+                .join("templates"),
+        }
+    }
+}
+
+pub fn render(template: &str, info: &ProblemInfo) -> String {
+    let created_at = info
+        .created_at
+        .map(|created_at| {
+            created_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default();
+    template
+        .replace("{{problem_name}}", info.problem_name.trim())
+        .replace("{{contest_name}}", info.contest_name.trim())
+        .replace("{{url}}", &info.url)
+        .replace("{{created_at}}", &created_at)
+}
+
+/// Copies templates of `config` into `dest`. Existing files are kept so that downloading
+/// a problem again doesn't overwrite a solution. Returns the created files.
+pub fn copy_templates(
+    config: &TemplateConfig,
+    info: &ProblemInfo,
+    dest: &Path,
+) -> Result<Vec<PathBuf>, anyhow::Error> {
+    let template_dir = config.dir();
+    if !template_dir.is_dir() {
+        return Ok(vec![]);
+    }
+    let language_dirs = if config.languages.is_empty() {
+        let mut dirs = std::fs::read_dir(&template_dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        dirs.sort();
+        dirs
+    } else {
+        config
+            .languages
+            .iter()
+            .map(|language| template_dir.join(language))
+            .collect()
+    };
+
+    let mut created = vec![];
+    for language_dir in language_dirs {
+        if !language_dir.is_dir() {
+            return Err(anyhow::anyhow!(
+                "No template directory: {}",
+                language_dir.to_string_lossy()
+            ));
+        }
+        for entry in walkdir::WalkDir::new(&language_dir)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|entry| entry.file_type().is_file())
+        {
+            let path = dest.join(entry.path().strip_prefix(&language_dir)?);
+            if path.exists() {
+                continue;
+            }
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let bytes = std::fs::read(entry.path())?;
+            // Binary files are copied as they are
+            match String::from_utf8(bytes) {
+                Ok(text) => std::fs::write(&path, render(&text, info))?,
+                Err(e) => std::fs::write(&path, e.into_bytes())?,
+            }
+            created.push(path);
+        }
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{copy_templates, render, TemplateConfig};
+    use crate::util::{problem_info, ProblemInfo, TempDir};
+
+    fn info() -> ProblemInfo {
+        problem_info(
+            "https://atcoder.jp/contests/abc154/tasks/abc154_a",
+            "AtCoder Beginner Contest 154\n",
+            "A - Remaining Balls",
+            None,
+        )
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(
+                "// {{contest_name}} {{problem_name}}\n// {{url}}\n// {{created_at}}\n// {{unknown}}",
+                &info()
+            ),
+            "// AtCoder Beginner Contest 154 A - Remaining Balls\n// https://atcoder.jp/contests/abc154/tasks/abc154_a\n// \n// {{unknown}}"
+        );
+    }
+
+    #[test]
+    fn test_copy_templates() {
+        let temp_dir = TempDir::new("template");
+        let root = temp_dir.path();
+        let templates = root.join("templates");
+        std::fs::create_dir_all(templates.join("cpp").join("lib")).unwrap();
+        std::fs::create_dir_all(templates.join("python")).unwrap();
+        std::fs::write(templates.join("cpp/main.cpp"), "// {{problem_name}}").unwrap();
+        std::fs::write(templates.join("cpp/lib/io.hpp"), "#pragma once").unwrap();
+        std::fs::write(templates.join("python/main.py"), "# {{url}}").unwrap();
+        let dest = root.join("abc154_a");
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(dest.join("main.py"), "print()").unwrap();
+
+        let mut config = TemplateConfig {
+            dir: Some(templates.to_string_lossy().to_string()),
+            languages: vec![],
+        };
+        let created = copy_templates(&config, &info(), &dest).unwrap();
+        assert_eq!(
+            created,
+            vec![dest.join("lib/io.hpp"), dest.join("main.cpp")]
+        );
+        assert_eq!(
+            std::fs::read_to_string(dest.join("main.cpp")).unwrap(),
+            "// A - Remaining Balls"
+        );
+        // Not overwritten
+        assert_eq!(
+            std::fs::read_to_string(dest.join("main.py")).unwrap(),
+            "print()"
+        );
+
+        config.languages = vec!["java".to_string()];
+        assert!(copy_templates(&config, &info(), &dest).is_err());
+    }
+}
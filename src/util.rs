@@ -1,12 +1,10 @@
-use chrono::serde::ts_seconds_option;
+use crate::checker::CheckerConfig;
+use crate::session::{self, CookieJar};
+use crate::submit::Verdict;
+use chrono::serde::{ts_seconds, ts_seconds_option};
 use chrono::Utc;
-use itertools::Itertools;
-use reqwest::header::{HeaderMap, HeaderValue, COOKIE};
 use serde::{Deserialize, Serialize};
-use std::{
-    io::{BufRead, Write},
-    path::Path,
-};
+use std::{io::Write, path::Path};
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ProblemInfo {
@@ -15,8 +13,191 @@ pub struct ProblemInfo {
     pub problem_name: String,
     #[serde(with = "ts_seconds_option")]
     pub created_at: Option<chrono::DateTime<Utc>>,
+    /// Time limit of the problem in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_limit_ms: Option<u64>,
+    /// Memory limit of the problem in megabytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_limit_mb: Option<u64>,
+    /// An absolute or relative error allowed in the output
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub float_tolerance: Option<f64>,
+    /// How outputs are judged. The float checker is used if only `float_tolerance` is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checker: Option<CheckerConfig>,
+    /// The contest ID given by the site. e.g `abc154`, `4`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contest_id: Option<String>,
+    /// The problem index in the contest given by the site. e.g `a`, `A`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub problem_index: Option<String>,
+    /// The difficulty on AtCoder Problems. Filled by `cpm sync-metadata`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<i64>,
+    /// The point value of the problem
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub point: Option<f64>,
+    /// When the contest of the problem started
+    #[serde(
+        default,
+        with = "ts_seconds_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub contest_start: Option<chrono::DateTime<Utc>>,
+    /// The last run of `cpm test`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_test: Option<TestRecord>,
+    /// Submissions seen by `cpm status`, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub submissions: Vec<SubmissionRecord>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TestRecord {
+    #[serde(with = "ts_seconds")]
+    pub ran_at: chrono::DateTime<Utc>,
+    pub passed: usize,
+    pub failed: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SubmissionRecord {
+    /// The submission ID on the site
+    pub id: String,
+    /// An abbreviation of the verdict. e.g `AC`, `WA`
+    pub verdict: String,
+    #[serde(with = "ts_seconds")]
+    pub checked_at: chrono::DateTime<Utc>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SolveStatus {
+    /// Neither tested nor submitted
+    Unsolved,
+    /// Tested or submitted without an accepted submission
+    Attempted,
+    /// Has an accepted submission
+    Solved,
+}
+
+impl std::str::FromStr for SolveStatus {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unsolved" => Ok(SolveStatus::Unsolved),
+            "attempted" => Ok(SolveStatus::Attempted),
+            "solved" => Ok(SolveStatus::Solved),
+            _ => Err(anyhow::anyhow!("Unknown status: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for SolveStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveStatus::Unsolved => write!(f, "unsolved"),
+            SolveStatus::Attempted => write!(f, "attempted"),
+            SolveStatus::Solved => write!(f, "solved"),
+        }
+    }
+}
+
+impl ProblemInfo {
+    pub fn solve_status(&self) -> SolveStatus {
+        if self
+            .submissions
+            .iter()
+            .any(|submission| Verdict::from_abbreviation(&submission.verdict) == Verdict::Accepted)
+        {
+            SolveStatus::Solved
+        } else if self.last_test.is_some() || !self.submissions.is_empty() {
+            SolveStatus::Attempted
+        } else {
+            SolveStatus::Unsolved
+        }
+    }
+    /// Takes the fields `cpm get` parsed from the page again. The rest, such as `checker`,
+    /// `created_at` and the history of tests and submissions, are kept.
+    pub fn refresh(&mut self, fetched: ProblemInfo) {
+        self.url = fetched.url;
+        self.contest_name = fetched.contest_name;
+        self.problem_name = fetched.problem_name;
+        self.time_limit_ms = fetched.time_limit_ms.or(self.time_limit_ms);
+        self.memory_limit_mb = fetched.memory_limit_mb.or(self.memory_limit_mb);
+        self.float_tolerance = fetched.float_tolerance.or(self.float_tolerance);
+        self.contest_id = fetched.contest_id.or(self.contest_id.take());
+        self.problem_index = fetched.problem_index.or(self.problem_index.take());
+    }
+    /// Adds a submission or updates its verdict. Returns whether `self` was changed.
+    pub fn record_submission(&mut self, id: &str, verdict: &Verdict) -> bool {
+        let verdict = verdict.to_string();
+        match self
+            .submissions
+            .iter_mut()
+            .find(|submission| submission.id == id)
+        {
+            Some(submission) if submission.verdict == verdict => false,
+            Some(submission) => {
+                submission.verdict = verdict;
+                submission.checked_at = Utc::now();
+                true
+            }
+            None => {
+                self.submissions.push(SubmissionRecord {
+                    id: id.to_string(),
+                    verdict,
+                    checked_at: Utc::now(),
+                });
+                true
+            }
+        }
+    }
+}
+
+/// Reads a JSON file. A missing file is `None`.
+pub fn load_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Option<T>, anyhow::Error> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let file = std::fs::File::open(path)?;
+    Ok(Some(serde_json::from_reader(std::io::BufReader::new(
+        file,
+    ))?))
+}
+/// Reads a JSON file. A missing file is the default value.
+pub fn load_json_or_default<T: serde::de::DeserializeOwned + Default>(
+    path: &Path,
+) -> Result<T, anyhow::Error> {
+    Ok(load_json(path)?.unwrap_or_default())
+}
+/// Writes `value` to a temporary file next to `path` and renames it, so that a crash never
+/// leaves a half-written file. The parent directories are created.
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), anyhow::Error> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(dir)?;
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid path: {}", path.display()))?;
+    let temporary = dir.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    let result = (|| -> Result<(), anyhow::Error> {
+        let mut file = std::fs::File::create(&temporary)?;
+        serde_json::to_writer(&mut file, value)?;
+        file.sync_all()?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temporary);
+    }
+    result
+}
 pub fn create_problem_info_json(info: ProblemInfo, path: &Path) -> Result<(), anyhow::Error> {
     let mut json_file = std::fs::OpenOptions::new()
         .write(true)
@@ -26,6 +207,12 @@ pub fn create_problem_info_json(info: ProblemInfo, path: &Path) -> Result<(), an
     json_file.write_all(serde_json::to_string(&info).unwrap().as_bytes())?;
     Ok(())
 }
+pub fn load_problem_info(path: &Path) -> Result<ProblemInfo, anyhow::Error> {
+    let file = std::fs::File::open(path.join(".problem.json"))?;
+    let reader = std::io::BufReader::new(file);
+    let info = serde_json::from_reader(reader)?;
+    Ok(info)
+}
 pub fn create_sample_test_files(
     test_cases: &[(String, String)],
     path: Option<&str>,
@@ -43,6 +230,7 @@ pub fn create_sample_test_files(
         let mut input_file = std::fs::OpenOptions::new()
             .write(true)
             .create(true)
+            .truncate(true)
             .open(root_path.join(input_file_name))?;
         input_file.write_all(input.as_bytes())?;
 
@@ -50,28 +238,87 @@ pub fn create_sample_test_files(
         let mut output_file = std::fs::OpenOptions::new()
             .write(true)
             .create(true)
+            .truncate(true)
             .open(root_path.join(output_file_name))?;
         output_file.write_all(output.as_bytes())?;
     }
     Ok(())
 }
-pub fn save_cookie_in_local(response: &reqwest::Response) -> Result<(), anyhow::Error> {
-    let cookies_str = response
-        .cookies()
-        .map(|cookie| format!("{}={}", cookie.name(), cookie.value()))
-        .join(";");
-    let path = dirs::home_dir().unwrap().join(".cpm");
-    //create $HOME/.cpm
-    std::fs::create_dir_all(path.clone())?;
-    //create cookie.jar under this directory
-    let cookie_path = path.join("cookie.jar");
-    std::fs::OpenOptions::new()
-        .write(true)
-        .create(true)
-        .open(cookie_path.clone())?
-        .write_all(cookies_str.as_bytes())?;
-    println!("SAVED YOUR COOKIE IN {}", cookie_path.to_str().unwrap());
-    Ok(())
+/// Pairs texts of a sample such as `<pre>`s, the input first and the output next.
+/// Each text ends with a newline even if the page omits it. An unpaired text is dropped.
+pub fn pair_samples(texts: Vec<String>) -> Vec<(String, String)> {
+    let with_newline = |text: &String| {
+        if text.ends_with('\n') {
+            text.clone()
+        } else {
+            format!("{}\n", text)
+        }
+    };
+    texts
+        .chunks_exact(2)
+        .map(|pair| (with_newline(&pair[0]), with_newline(&pair[1])))
+        .collect()
+}
+/// Splits a command line into words like a shell does.
+/// Single quotes, double quotes and backslashes can be used to keep spaces in a word.
+pub fn split_command(command: &str) -> Result<Vec<String>, anyhow::Error> {
+    let mut words = vec![];
+    let mut word = String::new();
+    // A word can be empty only if it's quoted. e.g ''
+    let mut in_word = false;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow::anyhow!("Unclosed quote: {}", command)),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c) if c == '"' || c == '\\' => word.push(c),
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err(anyhow::anyhow!("Unclosed quote: {}", command)),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(anyhow::anyhow!("Unclosed quote: {}", command)),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                if let Some(c) = chars.next() {
+                    word.push(c);
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
 }
 pub fn username_and_password() -> (String, String) {
     println!("Please input Your username and password");
@@ -79,14 +326,182 @@ pub fn username_and_password() -> (String, String) {
     let password = rpassword::prompt_password("Password > ").unwrap();
     (username, password)
 }
-pub fn local_cookie_headers() -> Result<HeaderMap, anyhow::Error> {
-    let cookiejar_path = dirs::home_dir().unwrap().join(".cpm").join("cookie.jar");
-    let file = std::fs::File::open(cookiejar_path)?;
-    let reader = std::io::BufReader::new(file);
+/// Loads `$HOME/.cpm/cookies.json`. If it doesn't exist yet, the AtCoder cookie saved by old versions
+/// in `$HOME/.cpm/cookie.jar` is imported.
+pub fn load_cookie_jar() -> Result<CookieJar, anyhow::Error> {
+    let path = session::cookie_jar_path();
+    if path.exists() {
+        return CookieJar::load(&path);
+    }
+    let jar = CookieJar::default();
+    let legacy_path = dirs::home_dir().unwrap().join(".cpm").join("cookie.jar");
+    if let Ok(header) = std::fs::read_to_string(legacy_path) {
+        jar.import_header("atcoder.jp", header.trim());
+    }
+    Ok(jar)
+}
+
+/// A `ProblemInfo` of tests with the other fields left to their defaults
+#[cfg(test)]
+pub(crate) fn problem_info(
+    url: &str,
+    contest_name: &str,
+    problem_name: &str,
+    created_at: Option<i64>,
+) -> ProblemInfo {
+    serde_json::from_value(serde_json::json!({
+        "url": url,
+        "contest_name": contest_name,
+        "problem_name": problem_name,
+        "created_at": created_at,
+    }))
+    .unwrap()
+}
+
+/// A directory of tests under the temporary directory. It's removed when dropped,
+/// even if an assertion fails.
+#[cfg(test)]
+pub(crate) struct TempDir(std::path::PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    pub(crate) fn new(name: &str) -> TempDir {
+        static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+        let id = COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let path = std::env::temp_dir().join(format!("cpm-{}-{}-{}", name, std::process::id(), id));
+        std::fs::create_dir_all(&path).unwrap();
+        // The index keys and the layout compare canonicalized paths
+        TempDir(path.canonicalize().unwrap())
+    }
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
 
-    let mut cookie_headers = HeaderMap::new();
-    reader.lines().map(|line| line.unwrap()).for_each(|line| {
-        cookie_headers.insert(COOKIE, HeaderValue::from_str(&line).unwrap());
-    });
-    Ok(cookie_headers)
+#[cfg(test)]
+mod tests {
+    use super::{
+        load_json, load_json_or_default, pair_samples, save_json, split_command, ProblemInfo,
+        SolveStatus, TempDir, TestRecord,
+    };
+    use crate::submit::Verdict;
+
+    #[test]
+    fn test_split_command() {
+        assert_eq!(
+            split_command("python3  main.py").unwrap(),
+            vec!["python3", "main.py"]
+        );
+        assert_eq!(
+            split_command(r#"sh -c 'echo "a b"' "c d" e\ f ''"#).unwrap(),
+            vec!["sh", "-c", r#"echo "a b""#, "c d", "e f", ""]
+        );
+        assert_eq!(
+            split_command(r#""a \"quoted\" \n""#).unwrap(),
+            vec![r#"a "quoted" \n"#]
+        );
+        assert!(split_command("echo 'oops").is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_json() {
+        let dir = TempDir::new("json");
+        let path = dir.path().join("a").join("index.json");
+        assert!(load_json_or_default::<Vec<u32>>(&path).unwrap().is_empty());
+        save_json(&path, &[1u32, 2, 3]).unwrap();
+        save_json(&path, &[4u32]).unwrap();
+        save_json(&path, &vec![4]).unwrap();
+        assert_eq!(load_json::<Vec<u32>>(&path).unwrap(), Some(vec![4]));
+        // No temporary files are left
+        assert_eq!(
+            std::fs::read_dir(path.parent().unwrap()).unwrap().count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_refresh() {
+        use crate::checker::CheckerConfig;
+        use chrono::{TimeZone, Utc};
+
+        let dir = TempDir::new("refresh");
+        let mut info = super::problem_info(
+            "https://atcoder.jp/contests/abc154/tasks/abc154_a",
+            "ABC154",
+            "A - Remaining Balls",
+            Some(1581249600),
+        );
+        info.time_limit_ms = Some(2000);
+        info.checker = Some(CheckerConfig::Special {
+            command: "./checker".to_string(),
+        });
+        info.record_submission("1", &Verdict::Accepted);
+        super::create_problem_info_json(info, dir.path()).unwrap();
+
+        let mut fetched = super::problem_info(
+            "https://atcoder.jp/contests/abc154/tasks/abc154_a",
+            "AtCoder Beginner Contest 154",
+            "A - Remaining Balls",
+            Some(1700000000),
+        );
+        fetched.time_limit_ms = Some(1000);
+        let mut info = super::load_problem_info(dir.path()).unwrap();
+        info.refresh(fetched);
+        super::create_problem_info_json(info, dir.path()).unwrap();
+
+        let info = super::load_problem_info(dir.path()).unwrap();
+        assert_eq!(info.contest_name, "AtCoder Beginner Contest 154");
+        assert_eq!(info.time_limit_ms, Some(1000));
+        assert_eq!(
+            info.checker,
+            Some(CheckerConfig::Special {
+                command: "./checker".to_string()
+            })
+        );
+        assert_eq!(info.created_at, Utc.timestamp_opt(1581249600, 0).single());
+        assert_eq!(info.solve_status(), SolveStatus::Solved);
+    }
+
+    #[test]
+    fn test_pair_samples() {
+        assert_eq!(
+            pair_samples(vec!["1 2".to_string(), "3\n".to_string(), "4".to_string()]),
+            vec![("1 2\n".to_string(), "3\n".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_solve_status() {
+        let mut info: ProblemInfo = serde_json::from_str(
+            r#"{"url": "https://atcoder.jp/contests/abc154/tasks/abc154_a", "contest_name": "ABC154", "problem_name": "A", "created_at": null}"#,
+        )
+        .unwrap();
+        assert_eq!(info.solve_status(), SolveStatus::Unsolved);
+        info.last_test = Some(TestRecord {
+            ran_at: chrono::Utc::now(),
+            passed: 1,
+            failed: 2,
+        });
+        assert_eq!(info.solve_status(), SolveStatus::Attempted);
+        assert!(info.record_submission("1", &Verdict::Judging));
+        assert!(!info.record_submission("1", &Verdict::Judging));
+        assert!(info.record_submission("1", &Verdict::WrongAnswer));
+        assert_eq!(info.solve_status(), SolveStatus::Attempted);
+        assert!(info.record_submission("2", &Verdict::Accepted));
+        assert_eq!(info.submissions.len(), 2);
+        assert_eq!(info.solve_status(), SolveStatus::Solved);
+
+        let json = serde_json::to_string(&info).unwrap();
+        let loaded: ProblemInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.submissions[0].verdict, "WA");
+        assert_eq!(loaded.submissions[1].verdict, "AC");
+        assert_eq!(loaded.last_test.unwrap().failed, 2);
+    }
 }
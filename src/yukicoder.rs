@@ -0,0 +1,187 @@
+use crate::parser::{self, Parser};
+use crate::site::{self, Site};
+use crate::util;
+use scraper::Selector;
+
+pub struct YukicoderParser {
+    document: String,
+}
+
+impl YukicoderParser {
+    pub fn new(html: &str) -> YukicoderParser {
+        YukicoderParser {
+            document: html.to_string(),
+        }
+    }
+    /// Problems of a contest page in the order of the table. e.g `/problems/no/1`
+    pub fn problem_url_list(&self) -> Vec<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse(r#"a[href^="/problems/"]"#).expect("invalid selector");
+
+        let mut problem_url_list = vec![];
+        for url in document
+            .select(&selector)
+            .filter_map(|element| element.value().attr("href"))
+        {
+            let paths = url.split('/').collect::<Vec<_>>();
+            // /problems/no/1 or /problems/1234 but not /problems/no/1/submit
+            let is_problem = match paths.as_slice() {
+                ["", "problems", "no", id] | ["", "problems", id] => {
+                    !id.is_empty() && id.chars().all(|c| c.is_ascii_digit())
+                }
+                _ => false,
+            };
+            if is_problem && !problem_url_list.iter().any(|problem| problem == url) {
+                problem_url_list.push(url.to_string());
+            }
+        }
+        problem_url_list
+    }
+    // e.g "実行時間制限 : 1ケース 5.000秒 / メモリ制限 : 512 MB / 標準ジャッジ問題" -> "5.000秒" for "実行時間制限"
+    fn limit(&self, name: &str) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("#content").expect("invalid selector");
+        let text = document
+            .select(&selector)
+            .next()?
+            .text()
+            .collect::<String>();
+        let limit = text[text.find(name)? + name.len()..]
+            .split('/')
+            .next()?
+            .trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+        let limit = limit.split_once("ケース").map_or(limit, |(_, limit)| limit);
+        Some(limit.trim().to_string())
+    }
+}
+
+impl Parser for YukicoderParser {
+    fn problem_name(&self) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("#content h3").expect("invalid selector");
+        document
+            .select(&selector)
+            .next()
+            .map(|title| title.text().collect::<String>().trim().to_string())
+    }
+    fn contest_name(&self) -> Option<String> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector =
+            Selector::parse(r#"#content a[href^="/contests/"]"#).expect("invalid selector");
+        // Some problems don't belong to any contest
+        let contest_name = document
+            .select(&selector)
+            .next()
+            .map(|contest| contest.text().collect::<String>().trim().to_string())
+            .unwrap_or_else(|| "yukicoder".to_string());
+        Some(contest_name)
+    }
+    fn sample_cases(&self) -> Vec<(String, String)> {
+        let document = scraper::Html::parse_document(&self.document);
+        let sample_selector = Selector::parse("div.sample").expect("invalid selector");
+        let pre_selector = Selector::parse("pre").expect("invalid selector");
+        document
+            .select(&sample_selector)
+            .filter_map(|sample| {
+                let pres = sample
+                    .select(&pre_selector)
+                    .map(|pre| pre.text().collect::<String>())
+                    .take(2)
+                    .collect();
+                util::pair_samples(pres).pop()
+            })
+            .collect()
+    }
+    fn time_limit_ms(&self) -> Option<u64> {
+        self.limit("実行時間制限")
+            .and_then(|limit| parser::parse_time_limit_ms(&limit))
+    }
+    fn memory_limit_mb(&self) -> Option<u64> {
+        self.limit("メモリ制限")
+            .and_then(|limit| parser::parse_memory_limit_mb(&limit))
+    }
+    fn float_tolerance(&self) -> Option<f64> {
+        let document = scraper::Html::parse_document(&self.document);
+        let selector = Selector::parse("#content_body").expect("invalid selector");
+        document.select(&selector).next().and_then(|statement| {
+            parser::parse_float_tolerance(&statement.text().collect::<String>())
+        })
+    }
+}
+
+pub struct YukicoderSite;
+
+impl Site for YukicoderSite {
+    fn name(&self) -> &'static str {
+        "yukicoder"
+    }
+    fn matches(&self, host: &str) -> bool {
+        host == "yukicoder.me"
+    }
+    fn parser(&self, document: &str) -> Result<Box<dyn Parser>, anyhow::Error> {
+        Ok(Box::new(YukicoderParser::new(document)))
+    }
+    // Problems don't belong to a contest in the URL. `/problems/no/1` -> ("no", "1"),
+    // and `/problems/1234`, whose number is an internal ID, -> ("problems", "1234")
+    fn contest_and_index(&self, url: &url::Url) -> Option<(String, String)> {
+        match url.path_segments()?.collect::<Vec<_>>().as_slice() {
+            ["problems", "no", number] => Some(("no".to_string(), number.to_string())),
+            ["problems", id] => Some(("problems".to_string(), id.to_string())),
+            _ => None,
+        }
+    }
+    // e.g https://yukicoder.me/contests/123
+    fn problem_url_list(&self, url: &url::Url, document: &str) -> Option<Vec<url::Url>> {
+        if !url.path().starts_with("/contests/") {
+            return None;
+        }
+        let url_list = YukicoderParser::new(document).problem_url_list();
+        Some(site::join_all(url, &url_list))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::YukicoderParser;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_fixture() {
+        let parser = YukicoderParser::new(include_str!("../tests/fixtures/yukicoder/no_1.html"));
+        assert_eq!(
+            parser.problem_name(),
+            Some("No.1 道のショートカット".to_string())
+        );
+        assert_eq!(
+            parser.contest_name(),
+            Some("yukicoder contest 1".to_string())
+        );
+        assert_eq!(parser.time_limit_ms(), Some(5000));
+        assert_eq!(parser.memory_limit_mb(), Some(512));
+        assert_eq!(parser.float_tolerance(), None);
+        assert_eq!(
+            parser.sample_cases(),
+            vec![
+                (
+                    "3\n100\n3\n1 2 1\n2 3 3\n10 90 10\n10 10 50\n".to_string(),
+                    "20\n".to_string()
+                ),
+                (
+                    "3\n100\n3\n1 2 1\n2 3 3\n1 100 10\n10 10 50\n".to_string(),
+                    "50\n".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_problem_url_list() {
+        let parser =
+            YukicoderParser::new(include_str!("../tests/fixtures/yukicoder/contest_1.html"));
+        assert_eq!(
+            parser.problem_url_list(),
+            vec!["/problems/no/1", "/problems/no/10", "/problems/no/2"]
+        );
+        assert_eq!(parser.contest_name(), Some("yukicoder".to_string()));
+    }
+}